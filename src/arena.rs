@@ -0,0 +1,155 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Emission of recursive types as arenas, given by `#[rustifact(arena = "NodeId")]`.
+//!
+//! The values reachable from the roots are emitted in breadth-first order as the elements of a
+//! single slice, with the fields linking a value to others of the type (`Box<Self>`,
+//! `Option<Box<Self>>` and `Vec<Self>` or `Vec<Box<Self>>`) emitted as indices into it, wrapped in
+//! the given index type. As the children of each value are visited in field order, they occupy
+//! consecutive indices, starting from those recorded when the value was visited.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{DataStruct, Fields, Ident, Path, Type};
+
+use crate::attr::{ContainerAttrs, FieldAttrs};
+use crate::emit::{type_args, ungroup, value_toks};
+use crate::{out_field_ident, out_path_binding, out_path_toks};
+
+enum Link {
+    One,
+    Opt,
+    Many,
+}
+
+fn is_type(ty: &Type, in_type: &Ident) -> bool {
+    matches!(ungroup(ty), Type::Path(tp) if tp.qself.is_none()
+        && tp.path.segments.last().is_some_and(|s| s.ident == *in_type))
+}
+
+fn boxed(ty: &Type, in_type: &Ident) -> bool {
+    type_args(ty, &["Box"]).is_some_and(|args| args.first().is_some_and(|t| is_type(t, in_type)))
+}
+
+/// How a field of type `ty` links to other values of `in_type`, if it does.
+fn link(ty: &Type, in_type: &Ident) -> Option<Link> {
+    if boxed(ty, in_type) {
+        return Some(Link::One);
+    }
+    if let Some(args) = type_args(ty, &["Option"]) {
+        return boxed(args.first()?, in_type).then_some(Link::Opt);
+    }
+    let elem = type_args(ty, &["Vec"])?.first().copied()?;
+    (is_type(elem, in_type) || boxed(elem, in_type)).then_some(Link::Many)
+}
+
+/// The inherent method `emit_arena`, emitting the arena of the values reachable from `roots`.
+pub(crate) fn arena_method(
+    out_type: &Ident,
+    data: &DataStruct,
+    attrs: &ContainerAttrs,
+    id: &Path,
+    in_type: &Ident,
+) -> syn::Result<TokenStream> {
+    let Fields::Named(named) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            id,
+            "`arena` is only supported on structs with named fields",
+        ));
+    };
+    let out_path = out_path_toks(out_type, attrs);
+    let binding = out_path_binding(out_type, attrs);
+    let next = Ident::new("next", Span::call_site());
+    let k = Ident::new("k", Span::call_site());
+    let mut push = TokenStream::new();
+    let mut init = TokenStream::new();
+    let mut fields = TokenStream::new();
+    let mut any_link = false;
+    for f in named.named.iter() {
+        let field_attrs = FieldAttrs::from_attrs(&f.attrs)?;
+        let ident = f.ident.as_ref().unwrap();
+        let out_ident = out_field_ident(ident, attrs);
+        let index = quote! {{
+            let #k = #next as u32;
+            #next += 1;
+            rustifact::internal::quote! { #id(##k) }
+        }};
+        let value = match link(&f.ty, in_type) {
+            Some(Link::One) => {
+                push.extend(quote! { nodes.push(&*node.#ident); });
+                index
+            }
+            Some(Link::Opt) => {
+                push.extend(quote! {
+                    if let ::core::option::Option::Some(c) = &node.#ident {
+                        nodes.push(&**c);
+                    }
+                });
+                quote! {
+                    match &node.#ident {
+                        ::core::option::Option::Some(_) => {
+                            let #k = #index;
+                            rustifact::internal::quote! { Some(##k) }
+                        }
+                        ::core::option::Option::None => rustifact::internal::quote! { None },
+                    }
+                }
+            }
+            Some(Link::Many) => {
+                push.extend(quote! {
+                    for c in &node.#ident {
+                        nodes.push(::core::borrow::Borrow::borrow(c));
+                    }
+                });
+                let ids = Ident::new("ids", Span::call_site());
+                quote! {{
+                    let mut #ids = rustifact::internal::TokenStream::new();
+                    for _ in &node.#ident {
+                        let #k = #index;
+                        #ids.extend(rustifact::internal::quote! { ##k, });
+                    }
+                    rustifact::internal::quote! { &[##ids] }
+                }}
+            }
+            None => {
+                let field = ident.to_string();
+                value_toks(&f.ty, quote! { &node.#ident }, &field, &field_attrs, attrs)
+            }
+        };
+        any_link |= link(&f.ty, in_type).is_some();
+        let cfg = field_attrs.cfg.as_ref().map(|cfg| quote! { #[cfg(#cfg)] });
+        init.extend(quote! { let #ident = #value; });
+        fields.extend(quote! { #cfg #out_ident: ##ident, });
+    }
+    if !any_link {
+        return Err(syn::Error::new_spanned(
+            id,
+            "`arena` requires a field of type Box<Self>, Option<Box<Self>> or Vec<Self>",
+        ));
+    }
+    let elems = Ident::new("elems", Span::call_site());
+    Ok(quote! {
+        /// The values reachable from `roots` as a single slice, in breadth-first order starting
+        /// with the roots, where links between values are indices into the slice.
+        pub fn emit_arena(roots: &[Self]) -> rustifact::internal::TokenStream {
+            #binding
+            let mut nodes: Vec<&Self> = roots.iter().collect();
+            let mut first = Vec::new();
+            let mut i = 0;
+            while i < nodes.len() {
+                let node = nodes[i];
+                first.push(nodes.len());
+                #push
+                i += 1;
+            }
+            let mut #elems = rustifact::internal::TokenStream::new();
+            for (node, mut #next) in nodes.into_iter().zip(first) {
+                #init
+                #elems.extend(rustifact::internal::quote! { #out_path { #fields }, });
+            }
+            rustifact::internal::quote! { &[##elems] }
+        }
+    })
+}
@@ -0,0 +1,872 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Parsing of `#[rustifact(...)]` attributes.
+
+use proc_macro2::TokenStream;
+use syn::parse::Parse;
+use syn::{parenthesized, Attribute, Ident, LitInt, LitStr, Path, PathArguments, Type, TypePath};
+
+use crate::case::Case;
+use crate::convert::Converter;
+use crate::emit::Radix;
+use crate::encode::{Compression, Encoding};
+
+const CONTAINER_KEYS: &[&str] = &[
+    "encode",
+    "encoder",
+    "compress",
+    "bake",
+    "bindgen",
+    "strip_prefix",
+    "union_field",
+    "owned",
+    "version",
+    "fingerprint",
+    "key",
+    "stream",
+    "checksum",
+    "metrics",
+    "all_variants",
+    "chain",
+    "unsuffixed",
+    "usize_as",
+    "bitpack",
+    "bulk",
+    "as_str",
+    "with",
+    "path_prefix",
+    "path_prefix_env",
+    "rename_all_variants",
+    "base",
+    "fallback",
+    "validate",
+    "arena",
+    "writer",
+    "group",
+    "impl_for",
+    "impl_cfg",
+    "msrv",
+    "max_tokens",
+    "context",
+];
+
+const VARIANT_KEYS: &[&str] = &["rename", "other", "as_other"];
+
+const FIELD_KEYS: &[&str] = &[
+    "cfg",
+    "with",
+    "unchecked",
+    "float_bits",
+    "unsuffixed",
+    "usize_as",
+    "owned",
+    "radix",
+    "bitflags",
+    "string_like",
+    "cow",
+    "seed",
+    "unwrap_or_default",
+    "expect",
+    "iter",
+    "collection",
+    "validate",
+    "unique_key",
+    "runtime_init",
+    "dyn_with",
+    "fn_path",
+    "fn_path_of",
+    "chunk",
+    "chunk_elem",
+    "pad_to",
+    "pad_with",
+    "array_len",
+    "min_len",
+    "max_len",
+    "sidecar",
+    "array",
+    "dedup",
+    "sort_by",
+    "sorted",
+    "sparse",
+    "multimap",
+    "trie",
+    "lookup",
+    "state_machine",
+    "runs",
+    "slice",
+    "static_ref",
+    "hoist",
+    "context",
+    "with_context",
+];
+
+/// The number of single character insertions, deletions and substitutions taking `a` to `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let sub = prev[j] + usize::from(ca != *cb);
+            row.push(sub.min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+/// The closest of `known` to `name`, if it's close enough to be a likely misspelling.
+fn suggestion<'a>(name: &str, known: &[&'a str]) -> Option<&'a str> {
+    known
+        .iter()
+        .map(|k| (edit_distance(name, k), *k))
+        .min()
+        .filter(|(d, _)| *d <= (name.len() / 3).max(1))
+        .map(|(_, k)| k)
+}
+
+/// The error for an unknown attribute of `kind`, suggesting the closest of `known`, if any.
+fn unknown(meta: &syn::meta::ParseNestedMeta, kind: &str, known: &[&str]) -> syn::Error {
+    let name = meta
+        .path
+        .get_ident()
+        .map(|i| i.to_string())
+        .unwrap_or_default();
+    match suggestion(&name, known) {
+        Some(k) => meta.error(format!(
+            "unknown rustifact {} attribute `{}`, did you mean `{}`?",
+            kind, name, k
+        )),
+        None => meta.error(format!("unknown rustifact {} attribute", kind)),
+    }
+}
+
+/// The fixed-width integer type named by `lit`, for `usize_as`.
+fn fixed_width(lit: &LitStr) -> syn::Result<Ident> {
+    const WIDTHS: &[&str] = &[
+        "u8", "u16", "u32", "u64", "u128", "i8", "i16", "i32", "i64", "i128",
+    ];
+    let name = lit.value();
+    if !WIDTHS.contains(&name.as_str()) {
+        let msg = format!(
+            "unknown integer type '{}', expected one of: {}",
+            name,
+            WIDTHS.join(", ")
+        );
+        return Err(syn::Error::new(lit.span(), msg));
+    }
+    Ok(Ident::new(&name, lit.span()))
+}
+
+/// Options given by `#[rustifact(...)]` on the struct or enum itself.
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    /// The type named by `#[OutType(...)]`.
+    pub out_type: Option<Ident>,
+    /// The generic arguments of the out type, such as the `<'static>` of `Foo<'static>`.
+    pub out_args: PathArguments,
+    /// The names and types of the fields of the out type, given by `#[OutFields(...)]`.
+    pub out_fields: Option<Vec<(Ident, Type)>>,
+    pub encode: Option<Encoding>,
+    /// The compression of encoded bytes, given by `compress = "..."`.
+    pub compress: Option<Compression>,
+    pub bake: bool,
+    pub bindgen: bool,
+    pub strip_prefix: Option<String>,
+    pub union_field: Option<Ident>,
+    pub owned: bool,
+    pub version: Option<u32>,
+    pub fingerprint: bool,
+    pub key: Option<Ident>,
+    pub stream: bool,
+    pub bulk: bool,
+    pub unsuffixed: bool,
+    /// The fixed-width integer type as which `usize` and `isize` values are emitted, given by
+    /// `usize_as = "u32"`.
+    pub usize_as: Option<Ident>,
+    /// The field of the out type into which bool fields are packed, given by `bitpack` or
+    /// `bitpack = "name"`.
+    pub bitpack: Option<Ident>,
+    pub checksum: bool,
+    pub metrics: bool,
+    pub all_variants: bool,
+    pub chain: bool,
+    pub as_str: bool,
+    pub group: Option<String>,
+    pub writer: bool,
+    /// A module providing `to_toks(&T, &mut TokenStream)`, given by `with = "module"`.
+    pub with: Option<Path>,
+    /// The tokens emitted for unknown variants of enums, given by `fallback = "..."`.
+    pub fallback: Option<TokenStream>,
+    /// A constant of the type, given by `base = "path"`, against which only differing fields are
+    /// emitted.
+    pub base: Option<Path>,
+    pub rename_all_variants: Option<Case>,
+    pub path_prefix: Option<String>,
+    /// The environment variable naming the path prefix at emission time, given by
+    /// `path_prefix_env = "VAR"`.
+    pub path_prefix_env: Option<String>,
+    /// A function `fn(&T) -> Result<(), E>` checking values before emission, given by
+    /// `validate = "path"`.
+    pub validate: Option<Path>,
+    /// The index type of the links between values emitted as arenas, given by `arena = "path"`.
+    pub arena: Option<Path>,
+    /// The concrete instantiations of a generic type for which the impls are derived, given by
+    /// `impl_for(Foo<u8>, ...)`, in place of impls generic over its parameters.
+    pub impl_for: Option<Vec<Type>>,
+    /// The approximate number of tokens beyond which emission of a value fails, given by
+    /// `max_tokens = N`.
+    pub max_tokens: Option<usize>,
+    /// The minor version of the oldest Rust 1.x for which artifacts must compile, given by
+    /// `msrv = "1.N"`.
+    pub msrv: Option<u32>,
+    /// The predicate of a `cfg` gating all the derived impls, given by `impl_cfg = "..."`.
+    pub impl_cfg: Option<TokenStream>,
+    /// The type of the context of emission taken by `to_toks_with`, given by `context = "path"`.
+    pub context: Option<Type>,
+    /// Whether the type is marked `#[non_exhaustive]`.
+    pub non_exhaustive: bool,
+}
+
+impl ContainerAttrs {
+    /// Whether artifacts may use what was stabilized in Rust `1.minor`.
+    pub fn supports(&self, minor: u32) -> bool {
+        self.msrv.map_or(true, |msrv| msrv >= minor)
+    }
+
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = ContainerAttrs::default();
+        for attr in attrs {
+            if attr.path().is_ident("non_exhaustive") {
+                out.non_exhaustive = true;
+            }
+            if attr.path().is_ident("OutType") {
+                let ty: TypePath = attr.parse_args()?;
+                let mut segments = ty.path.segments.into_iter();
+                match (ty.qself, segments.next(), segments.next()) {
+                    (None, Some(segment), None)
+                        if !matches!(segment.arguments, PathArguments::Parenthesized(_)) =>
+                    {
+                        out.out_type = Some(segment.ident);
+                        out.out_args = segment.arguments;
+                    }
+                    _ => return Err(syn::Error::new_spanned(
+                        attr,
+                        "the out type must be a single name, optionally with generic arguments; \
+                             use `path_prefix` for paths",
+                    )),
+                }
+            }
+            if attr.path().is_ident("OutFields") {
+                out.out_fields = Some(attr.parse_args_with(|input: syn::parse::ParseStream| {
+                    let mut fields = Vec::new();
+                    while !input.is_empty() {
+                        let ident: Ident = input.parse()?;
+                        input.parse::<syn::Token![:]>()?;
+                        fields.push((ident, input.parse()?));
+                        if !input.is_empty() {
+                            input.parse::<syn::Token![,]>()?;
+                        }
+                    }
+                    Ok(fields)
+                })?);
+            }
+            if !attr.path().is_ident("rustifact") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("encode") || meta.path.is_ident("encoder") {
+                    if out.encode.is_some() {
+                        return Err(meta.error("only one of `encode` and `encoder` may be given"));
+                    }
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.encode = Some(if meta.path.is_ident("encode") {
+                        Encoding::from_lit(&s)?
+                    } else {
+                        Encoding::from_encoder_lit(&s)?
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("compress") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.compress = Some(Compression::from_lit(&s)?);
+                    Ok(())
+                } else if meta.path.is_ident("bake") {
+                    out.bake = true;
+                    Ok(())
+                } else if meta.path.is_ident("bindgen") {
+                    out.bindgen = true;
+                    Ok(())
+                } else if meta.path.is_ident("strip_prefix") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.strip_prefix = Some(s.value());
+                    Ok(())
+                } else if meta.path.is_ident("union_field") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.union_field = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("owned") {
+                    out.owned = true;
+                    Ok(())
+                } else if meta.path.is_ident("version") {
+                    let n: LitInt = meta.value()?.parse()?;
+                    out.version = Some(n.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("fingerprint") {
+                    out.fingerprint = true;
+                    Ok(())
+                } else if meta.path.is_ident("key") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.key = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("stream") {
+                    out.stream = true;
+                    Ok(())
+                } else if meta.path.is_ident("checksum") {
+                    out.checksum = true;
+                    Ok(())
+                } else if meta.path.is_ident("chain") {
+                    out.chain = true;
+                    Ok(())
+                } else if meta.path.is_ident("all_variants") {
+                    out.all_variants = true;
+                    Ok(())
+                } else if meta.path.is_ident("metrics") {
+                    out.metrics = true;
+                    Ok(())
+                } else if meta.path.is_ident("unsuffixed") {
+                    out.unsuffixed = true;
+                    Ok(())
+                } else if meta.path.is_ident("usize_as") {
+                    out.usize_as = Some(fixed_width(&meta.value()?.parse()?)?);
+                    Ok(())
+                } else if meta.path.is_ident("bitpack") {
+                    out.bitpack = Some(if meta.input.peek(syn::Token![=]) {
+                        let s: LitStr = meta.value()?.parse()?;
+                        s.parse()?
+                    } else {
+                        Ident::new("bits", proc_macro2::Span::call_site())
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("bulk") {
+                    out.bulk = true;
+                    Ok(())
+                } else if meta.path.is_ident("as_str") {
+                    out.as_str = true;
+                    Ok(())
+                } else if meta.path.is_ident("with") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.with = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("path_prefix") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.path_prefix = Some(s.value());
+                    Ok(())
+                } else if meta.path.is_ident("path_prefix_env") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.path_prefix_env = Some(s.value());
+                    Ok(())
+                } else if meta.path.is_ident("rename_all_variants") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.rename_all_variants = Some(Case::from_lit(&s)?);
+                    Ok(())
+                } else if meta.path.is_ident("base") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.base = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("fallback") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.fallback = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("validate") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.validate = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("arena") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.arena = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("impl_for") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let types = content.parse_terminated(Type::parse, syn::Token![,])?;
+                    if types.is_empty() {
+                        return Err(meta.error("`impl_for` needs at least one type"));
+                    }
+                    out.impl_for = Some(types.into_iter().collect());
+                    Ok(())
+                } else if meta.path.is_ident("max_tokens") {
+                    let n: LitInt = meta.value()?.parse()?;
+                    out.max_tokens = Some(n.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("msrv") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    let value = s.value();
+                    let mut parts = value.split('.');
+                    out.msrv = match (parts.next(), parts.next().map(str::parse::<u32>)) {
+                        (Some("1"), Some(Ok(minor))) if parts.all(|p| p.parse::<u32>().is_ok()) => {
+                            Some(minor)
+                        }
+                        _ => {
+                            let msg = format!(
+                                "invalid Rust version '{}', expected one like \"1.65\"",
+                                value
+                            );
+                            return Err(syn::Error::new(s.span(), msg));
+                        }
+                    };
+                    Ok(())
+                } else if meta.path.is_ident("impl_cfg") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.impl_cfg = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("writer") {
+                    out.writer = true;
+                    Ok(())
+                } else if meta.path.is_ident("group") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.group = Some(s.value());
+                    Ok(())
+                } else if meta.path.is_ident("context") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.context = Some(s.parse()?);
+                    Ok(())
+                } else {
+                    Err(unknown(&meta, "container", CONTAINER_KEYS))
+                }
+            })?;
+        }
+        Ok(out)
+    }
+}
+
+/// Options given by `#[rustifact(...)]` on an enum variant.
+#[derive(Default)]
+pub(crate) struct VariantAttrs {
+    pub rename: Option<String>,
+    /// Whether the variant is the catch-all, as which variants marked `as_other` are emitted.
+    pub other: bool,
+    pub as_other: bool,
+}
+
+impl VariantAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = VariantAttrs::default();
+        for attr in attrs {
+            if !attr.path().is_ident("rustifact") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.rename = Some(s.value());
+                    Ok(())
+                } else if meta.path.is_ident("other") {
+                    out.other = true;
+                    Ok(())
+                } else if meta.path.is_ident("as_other") {
+                    out.as_other = true;
+                    Ok(())
+                } else {
+                    Err(unknown(&meta, "variant", VARIANT_KEYS))
+                }
+            })?;
+        }
+        Ok(out)
+    }
+}
+
+/// Options given by `#[rustifact(...)]` on a field.
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    /// The predicate of `cfg(...)`, attached to the field in the emitted constructor.
+    pub cfg: Option<TokenStream>,
+    /// The values of the field's doc comments, copied onto items emitted for it.
+    pub docs: Vec<LitStr>,
+    pub with: Option<Converter>,
+    /// Converters for positions of tuples, given by `with(0 = "...", 2 = "...")`.
+    pub with_positions: Vec<(usize, Converter)>,
+    pub unchecked: bool,
+    pub float_bits: bool,
+    /// As for the container, given by `usize_as = "..."`.
+    pub usize_as: Option<Ident>,
+    pub unsuffixed: bool,
+    pub radix: Option<Radix>,
+    pub owned: bool,
+    pub string_like: bool,
+    /// Whether `Cow` fields stay `Cow<'static, _>` in the out type, given by `cow`.
+    pub cow: bool,
+    pub seed: bool,
+    pub unwrap_or_default: bool,
+    /// The message with which the build fails for `None` or `Err` values, given by
+    /// `expect = "..."`.
+    pub expect: Option<LitStr>,
+    pub bitflags: bool,
+    /// Given by `static_ref` or `static_ref = "T"`, the latter being the out type of the value.
+    pub static_ref: Option<Option<Type>>,
+    /// Given by `hoist` or `hoist = "T"`, the latter being the out type of the value.
+    pub hoist: Option<Option<Type>>,
+    /// Whether the value is emitted by its own `to_toks_with`, within the context of the parent,
+    /// given by `context`.
+    pub context: bool,
+    /// A function `fn(&T, &Ctx) -> TokenStream` emitting the value within the context of the
+    /// parent, given by `with_context = "path"`.
+    pub with_context: Option<Path>,
+    pub slice: bool,
+    pub iter: bool,
+    pub collection: bool,
+    pub runs: bool,
+    pub sparse: bool,
+    pub multimap: bool,
+    pub trie: bool,
+    pub state_machine: bool,
+    /// Whether a map is emitted for lookups by `binary_search`, given by
+    /// `lookup = "binary_search"`.
+    pub lookup: bool,
+    pub sorted: bool,
+    /// The key of each `item`, or a comparator, by which collections are sorted, given by
+    /// `sort_by = "..."`.
+    pub sort_by: Option<syn::Expr>,
+    /// Given by `dedup` or `dedup = "..."`, the latter being the key of each `item` by which
+    /// duplicates are found.
+    pub dedup: Option<Option<syn::Expr>>,
+    pub array: bool,
+    pub sidecar: bool,
+    /// The number of elements in each static, given by `chunk = N`.
+    pub chunk: Option<usize>,
+    /// The out type of the elements of chunks, given by `chunk_elem = "T"`.
+    pub chunk_elem: Option<Type>,
+    /// The number of elements to which collections are padded, given by `pad_to = N`.
+    pub pad_to: Option<usize>,
+    /// The exact number of elements of collections emitted as arrays, given by `array_len = N`.
+    pub array_len: Option<usize>,
+    /// The build-side value with which collections are padded, given by `pad_with = "..."`.
+    pub pad_with: Option<syn::Expr>,
+    /// The bounds on the number of elements of collections, given by `min_len = A` and
+    /// `max_len = B`.
+    pub min_len: Option<usize>,
+    pub max_len: Option<usize>,
+    /// A function `fn(&T) -> Result<(), E>` checking the field before emission, given by
+    /// `validate = "path"`.
+    pub validate: Option<Path>,
+    /// The field of the elements of the collection that no two may share, given by
+    /// `unique_key = "field"`.
+    pub unique_key: Option<Ident>,
+    /// A function of the artifact's crate building the out value from the emitted one, given by
+    /// `runtime_init = "path"`.
+    pub runtime_init: Option<Path>,
+    /// A function `fn(&dyn Trait) -> TokenStream` emitting the values of `Box<dyn Trait>`, given by
+    /// `dyn_with = "path"`.
+    pub dyn_with: Option<Path>,
+    /// The path of the function emitted for function pointers, given by `fn_path = "path"`.
+    pub fn_path: Option<Path>,
+    /// A function `fn(&F) -> &str` giving the path of each function pointer, given by
+    /// `fn_path_of = "path"`.
+    pub fn_path_of: Option<Path>,
+}
+
+impl FieldAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = FieldAttrs::default();
+        for attr in attrs {
+            if let syn::Meta::NameValue(nv) = &attr.meta {
+                if let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(doc),
+                    ..
+                }) = &nv.value
+                {
+                    if nv.path.is_ident("doc") {
+                        out.docs.push(doc.clone());
+                    }
+                }
+            }
+            if !attr.path().is_ident("rustifact") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("cfg") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    out.cfg = Some(content.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("with") && meta.input.peek(syn::token::Paren) {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    while !content.is_empty() {
+                        let index: LitInt = content.parse()?;
+                        content.parse::<syn::Token![=]>()?;
+                        out.with_positions
+                            .push((index.base10_parse()?, Converter::parse(&content)?));
+                        if !content.is_empty() {
+                            content.parse::<syn::Token![,]>()?;
+                        }
+                    }
+                    Ok(())
+                } else if meta.path.is_ident("with") {
+                    out.with = Some(Converter::parse(meta.value()?)?);
+                    Ok(())
+                } else if meta.path.is_ident("unchecked") {
+                    out.unchecked = true;
+                    Ok(())
+                } else if meta.path.is_ident("float_bits") {
+                    out.float_bits = true;
+                    Ok(())
+                } else if meta.path.is_ident("unsuffixed") {
+                    out.unsuffixed = true;
+                    Ok(())
+                } else if meta.path.is_ident("usize_as") {
+                    out.usize_as = Some(fixed_width(&meta.value()?.parse()?)?);
+                    Ok(())
+                } else if meta.path.is_ident("owned") {
+                    out.owned = true;
+                    Ok(())
+                } else if meta.path.is_ident("radix") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.radix = Some(Radix::from_lit(&s)?);
+                    Ok(())
+                } else if meta.path.is_ident("bitflags") {
+                    out.bitflags = true;
+                    Ok(())
+                } else if meta.path.is_ident("unwrap_or_default") {
+                    out.unwrap_or_default = true;
+                    Ok(())
+                } else if meta.path.is_ident("expect") {
+                    out.expect = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("seed") {
+                    out.seed = true;
+                    Ok(())
+                } else if meta.path.is_ident("cow") {
+                    out.cow = true;
+                    Ok(())
+                } else if meta.path.is_ident("string_like") {
+                    out.string_like = true;
+                    Ok(())
+                } else if meta.path.is_ident("iter") {
+                    out.iter = true;
+                    Ok(())
+                } else if meta.path.is_ident("collection") {
+                    out.collection = true;
+                    Ok(())
+                } else if meta.path.is_ident("validate") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.validate = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("unique_key") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.unique_key = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("runtime_init") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.runtime_init = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("dyn_with") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.dyn_with = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("fn_path") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.fn_path = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("fn_path_of") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.fn_path_of = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("chunk") {
+                    let n: LitInt = meta.value()?.parse()?;
+                    let n: usize = n.base10_parse()?;
+                    if n == 0 {
+                        return Err(meta.error("chunks must have at least one element"));
+                    }
+                    out.chunk = Some(n);
+                    Ok(())
+                } else if meta.path.is_ident("pad_to") {
+                    let n: LitInt = meta.value()?.parse()?;
+                    out.pad_to = Some(n.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("min_len") {
+                    let n: LitInt = meta.value()?.parse()?;
+                    out.min_len = Some(n.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("max_len") {
+                    let n: LitInt = meta.value()?.parse()?;
+                    out.max_len = Some(n.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("array_len") {
+                    let n: LitInt = meta.value()?.parse()?;
+                    out.array_len = Some(n.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("pad_with") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.pad_with = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("chunk_elem") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.chunk_elem = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("sidecar") {
+                    out.sidecar = true;
+                    Ok(())
+                } else if meta.path.is_ident("array") {
+                    out.array = true;
+                    Ok(())
+                } else if meta.path.is_ident("dedup") {
+                    out.dedup = Some(if meta.input.peek(syn::Token![=]) {
+                        let s: LitStr = meta.value()?.parse()?;
+                        Some(s.parse()?)
+                    } else {
+                        None
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("sort_by") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.sort_by = Some(s.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("sorted") {
+                    out.sorted = true;
+                    Ok(())
+                } else if meta.path.is_ident("multimap") {
+                    out.multimap = true;
+                    Ok(())
+                } else if meta.path.is_ident("trie") {
+                    out.trie = true;
+                    Ok(())
+                } else if meta.path.is_ident("state_machine") {
+                    out.state_machine = true;
+                    Ok(())
+                } else if meta.path.is_ident("lookup") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    if s.value() != "binary_search" {
+                        let msg = format!(
+                            "unknown lookup '{}', expected one of: binary_search",
+                            s.value()
+                        );
+                        return Err(syn::Error::new(s.span(), msg));
+                    }
+                    out.lookup = true;
+                    Ok(())
+                } else if meta.path.is_ident("sparse") {
+                    out.sparse = true;
+                    Ok(())
+                } else if meta.path.is_ident("runs") {
+                    out.runs = true;
+                    Ok(())
+                } else if meta.path.is_ident("slice") {
+                    out.slice = true;
+                    Ok(())
+                } else if meta.path.is_ident("static_ref") || meta.path.is_ident("hoist") {
+                    let ty = if meta.input.peek(syn::Token![=]) {
+                        let s: LitStr = meta.value()?.parse()?;
+                        Some(s.parse()?)
+                    } else {
+                        None
+                    };
+                    if meta.path.is_ident("hoist") {
+                        out.hoist = Some(ty);
+                    } else {
+                        out.static_ref = Some(ty);
+                    }
+                    Ok(())
+                } else if meta.path.is_ident("context") {
+                    out.context = true;
+                    Ok(())
+                } else if meta.path.is_ident("with_context") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    out.with_context = Some(s.parse()?);
+                    Ok(())
+                } else {
+                    Err(unknown(&meta, "field", FIELD_KEYS))
+                }
+            })?;
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("sorted", "sorted"), 0);
+        assert_eq!(edit_distance("", "iter"), 4);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        // A transposition is two substitutions.
+        assert_eq!(edit_distance("renmae", "rename"), 2);
+    }
+
+    #[test]
+    fn suggestion_finds_close_keys() {
+        assert_eq!(suggestion("sortd", FIELD_KEYS), Some("sorted"));
+        assert_eq!(suggestion("encod", CONTAINER_KEYS), Some("encode"));
+        assert_eq!(suggestion("renam", VARIANT_KEYS), Some("rename"));
+    }
+
+    #[test]
+    fn suggestion_threshold_grows_with_length() {
+        // Names up to five characters allow a single edit.
+        assert_eq!(suggestion("ter", FIELD_KEYS), Some("iter"));
+        assert_eq!(suggestion("it", FIELD_KEYS), None);
+        // Longer names allow an edit per three characters.
+        assert_eq!(suggestion("runtim_nit", FIELD_KEYS), Some("runtime_init"));
+        assert_eq!(suggestion("rntm_nt", FIELD_KEYS), None);
+        assert_eq!(suggestion("unrelated", VARIANT_KEYS), None);
+    }
+
+    #[test]
+    fn unknown_keys_are_errors_with_suggestions() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[rustifact(sortd)])];
+        let err = FieldAttrs::from_attrs(&attrs).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "unknown rustifact field attribute `sortd`, did you mean `sorted`?"
+        );
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[rustifact(zzz)])];
+        let err = VariantAttrs::from_attrs(&attrs).err().unwrap();
+        assert_eq!(err.to_string(), "unknown rustifact variant attribute");
+    }
+
+    #[test]
+    fn known_keys_are_listed() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(
+            #[rustifact(sorted, hoist, trie, lookup = "binary_search")]
+        )];
+        let field = FieldAttrs::from_attrs(&attrs).unwrap();
+        assert!(field.sorted && field.hoist.is_some() && field.trie && field.lookup);
+        for key in FIELD_KEYS {
+            assert_eq!(suggestion(key, FIELD_KEYS), Some(*key));
+        }
+    }
+
+    #[test]
+    fn lookup_takes_known_strategies() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[rustifact(lookup = "hash")])];
+        let err = FieldAttrs::from_attrs(&attrs).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "unknown lookup 'hash', expected one of: binary_search"
+        );
+    }
+
+    #[test]
+    fn msrv_gates_features() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[rustifact(msrv = "1.79")])];
+        let container = ContainerAttrs::from_attrs(&attrs).unwrap();
+        assert_eq!(container.msrv, Some(79));
+        assert!(container.supports(79));
+        assert!(!container.supports(80));
+        assert!(ContainerAttrs::default().supports(80));
+    }
+
+    #[test]
+    fn usize_as_takes_fixed_width_integers() {
+        let lit: LitStr = parse_quote!("u32");
+        assert_eq!(fixed_width(&lit).unwrap(), "u32");
+        let lit: LitStr = parse_quote!("usize");
+        assert!(fixed_width(&lit).is_err());
+    }
+}
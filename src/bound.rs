@@ -0,0 +1,177 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! The `ToTokenStream` bounds of generic types.
+//!
+//! Each type parameter used directly in a field type, and each associated type of a type
+//! parameter so used (as in `T::Item`), is bounded by `ToTokenStream` in the derived impls.
+//! Parameters used only through their associated types, or only within `PhantomData`, are left
+//! unbounded, as are the types of fields emitted through a converter (`with`, `dyn_with` or
+//! `with_context`) or as a path (`fn_path` or `fn_path_of`).
+
+use crate::attr::FieldAttrs;
+use quote::ToTokens;
+use syn::{parse_quote, Data, GenericArgument, Generics, Ident, PathArguments, QSelf, Type};
+
+/// Collects the bounded types of `ty` into `out`.
+fn collect(ty: &Type, params: &[&Ident], out: &mut Vec<Type>) {
+    match ty {
+        Type::Path(tp) => {
+            let is_param = |t: &Type| {
+                matches!(t, Type::Path(p) if p.qself.is_none()
+                    && p.path.segments.len() == 1
+                    && params.iter().any(|param| p.path.is_ident(*param)))
+            };
+            let segs = &tp.path.segments;
+            let direct = tp.qself.is_none() && segs.len() == 1 && is_param(ty);
+            let assoc = match &tp.qself {
+                Some(QSelf { ty: self_ty, .. }) => is_param(self_ty),
+                None => segs.len() > 1 && params.iter().any(|p| segs[0].ident == **p),
+            };
+            if direct || assoc {
+                let key = ty.to_token_stream().to_string();
+                if !out.iter().any(|t| t.to_token_stream().to_string() == key) {
+                    out.push(ty.clone());
+                }
+                return;
+            }
+            let last = match segs.last() {
+                Some(last) if last.ident != "PhantomData" => last,
+                _ => return,
+            };
+            if let PathArguments::AngleBracketed(args) = &last.arguments {
+                for arg in &args.args {
+                    if let GenericArgument::Type(t) = arg {
+                        collect(t, params, out);
+                    }
+                }
+            }
+        }
+        Type::Reference(r) => collect(&r.elem, params, out),
+        Type::Array(a) => collect(&a.elem, params, out),
+        Type::Slice(s) => collect(&s.elem, params, out),
+        Type::Paren(p) => collect(&p.elem, params, out),
+        Type::Group(g) => collect(&g.elem, params, out),
+        Type::Ptr(p) => collect(&p.elem, params, out),
+        Type::Tuple(t) => {
+            for elem in &t.elems {
+                collect(elem, params, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `generics` with `ToTokenStream` bounds added to the where clause for the field types of `data`.
+pub(crate) fn with_bounds(generics: &Generics, data: &Data) -> Generics {
+    let params: Vec<&Ident> = generics.type_params().map(|p| &p.ident).collect();
+    let mut generics = generics.clone();
+    if params.is_empty() {
+        return generics;
+    }
+    let mut bounded = Vec::new();
+    let fields: Vec<&syn::Field> = match data {
+        Data::Struct(data) => data.fields.iter().collect(),
+        Data::Enum(data) => data.variants.iter().flat_map(|v| v.fields.iter()).collect(),
+        Data::Union(data) => data.fields.named.iter().collect(),
+    };
+    for f in fields {
+        let converted = FieldAttrs::from_attrs(&f.attrs).is_ok_and(|a| {
+            a.with.is_some()
+                || a.dyn_with.is_some()
+                || a.with_context.is_some()
+                || a.fn_path.is_some()
+                || a.fn_path_of.is_some()
+        });
+        if !converted {
+            collect(&f.ty, &params, &mut bounded);
+        }
+    }
+    let where_clause = generics.make_where_clause();
+    for ty in bounded {
+        where_clause
+            .predicates
+            .push(parse_quote! { #ty: rustifact::ToTokenStream });
+    }
+    generics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::DeriveInput;
+
+    fn predicates(input: DeriveInput) -> Vec<String> {
+        let generics = with_bounds(&input.generics, &input.data);
+        match &generics.where_clause {
+            Some(w) => w
+                .predicates
+                .iter()
+                .map(|p| p.to_token_stream().to_string())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn bounds_params_used_in_fields() {
+        let input: DeriveInput = parse_quote! {
+            struct S<T, U> { a: Vec<T>, b: Option<(T, U)> }
+        };
+        assert_eq!(
+            predicates(input),
+            [
+                "T : rustifact :: ToTokenStream",
+                "U : rustifact :: ToTokenStream"
+            ]
+        );
+    }
+
+    #[test]
+    fn bounds_associated_types_and_skips_phantoms() {
+        let input: DeriveInput = parse_quote! {
+            struct S<T: Cfg, U> where T::Item: Clone {
+                x: T::Item,
+                y: <T as Cfg>::Other,
+                z: PhantomData<U>,
+            }
+        };
+        assert_eq!(
+            predicates(input),
+            [
+                "T :: Item : Clone",
+                "T :: Item : rustifact :: ToTokenStream",
+                "< T as Cfg > :: Other : rustifact :: ToTokenStream"
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_converted_fields() {
+        let input: DeriveInput = parse_quote! {
+            struct S<T, U, V, W, X, Y> {
+                #[rustifact(with = "t_toks")]
+                t: T,
+                #[rustifact(dyn_with = "u_toks")]
+                u: Box<U>,
+                #[rustifact(with_context = "w_toks")]
+                w: W,
+                #[rustifact(fn_path = "x_fn")]
+                x: fn(X),
+                #[rustifact(fn_path_of = "y_path")]
+                y: Option<fn(&Y) -> Y>,
+                v: V,
+            }
+        };
+        assert_eq!(predicates(input), ["V : rustifact :: ToTokenStream"]);
+    }
+
+    #[test]
+    fn leaves_lifetime_only_types_unchanged() {
+        let input: DeriveInput = parse_quote! {
+            struct S<'a, 'b: 'a> { a: &'a str, b: &'b [u8] }
+        };
+        assert!(predicates(input).is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Conversion of names between naming conventions, for `rename_all_variants`.
+
+use syn::LitStr;
+
+#[derive(Clone, Copy)]
+pub(crate) enum Case {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+const CASES: &[(&str, Case)] = &[
+    ("lowercase", Case::Lower),
+    ("UPPERCASE", Case::Upper),
+    ("PascalCase", Case::Pascal),
+    ("camelCase", Case::Camel),
+    ("snake_case", Case::Snake),
+    ("SCREAMING_SNAKE_CASE", Case::ScreamingSnake),
+    ("kebab-case", Case::Kebab),
+    ("SCREAMING-KEBAB-CASE", Case::ScreamingKebab),
+];
+
+/// The words of `name`, split at underscores, hyphens and changes of case, so that both
+/// `HttpServer` and `HTTP_SERVER` give `HTTP` or `Http`, then `SERVER` or `Server`.
+fn words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '_' || c == '-' {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !word.is_empty() {
+            let prev_lower = chars[i - 1].is_lowercase() || chars[i - 1].is_numeric();
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            let prev_upper = chars[i - 1].is_uppercase();
+            if prev_lower || (prev_upper && next_lower) {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+impl Case {
+    pub fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        let name = lit.value();
+        match CASES.iter().find(|(n, _)| *n == name) {
+            Some((_, case)) => Ok(*case),
+            None => {
+                let known: Vec<&str> = CASES.iter().map(|(n, _)| *n).collect();
+                Err(syn::Error::new(
+                    lit.span(),
+                    format!(
+                        "unknown case '{}', expected one of: {}",
+                        name,
+                        known.join(", ")
+                    ),
+                ))
+            }
+        }
+    }
+
+    /// `name` converted to the case.
+    pub fn apply(self, name: &str) -> String {
+        let words = words(name);
+        let joined = |sep: &str, f: fn(&str) -> String| -> String {
+            words.iter().map(|w| f(w)).collect::<Vec<_>>().join(sep)
+        };
+        match self {
+            Case::Lower => joined("", |w| w.to_lowercase()),
+            Case::Upper => joined("", |w| w.to_uppercase()),
+            Case::Pascal => joined("", capitalize),
+            Case::Camel => {
+                let pascal = joined("", capitalize);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_lowercase().chain(chars).collect(),
+                    None => pascal,
+                }
+            }
+            Case::Snake => joined("_", |w| w.to_lowercase()),
+            Case::ScreamingSnake => joined("_", |w| w.to_uppercase()),
+            Case::Kebab => joined("-", |w| w.to_lowercase()),
+            Case::ScreamingKebab => joined("-", |w| w.to_uppercase()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn words_split_at_separators_and_case() {
+        assert_eq!(words("HttpServer"), ["Http", "Server"]);
+        assert_eq!(words("HTTP_SERVER"), ["HTTP", "SERVER"]);
+        assert_eq!(words("HTTPServer"), ["HTTP", "Server"]);
+        assert_eq!(words("http-server"), ["http", "server"]);
+        assert_eq!(words("Utf8Error"), ["Utf8", "Error"]);
+        assert_eq!(words("__a__b__"), ["a", "b"]);
+    }
+
+    #[test]
+    fn apply_converts_between_cases() {
+        let name = "HttpServer";
+        assert_eq!(Case::Lower.apply(name), "httpserver");
+        assert_eq!(Case::Upper.apply(name), "HTTPSERVER");
+        assert_eq!(Case::Pascal.apply("HTTP_SERVER"), "HttpServer");
+        assert_eq!(Case::Camel.apply(name), "httpServer");
+        assert_eq!(Case::Snake.apply(name), "http_server");
+        assert_eq!(Case::ScreamingSnake.apply(name), "HTTP_SERVER");
+        assert_eq!(Case::Kebab.apply(name), "http-server");
+        assert_eq!(Case::ScreamingKebab.apply(name), "HTTP-SERVER");
+        assert_eq!(Case::Camel.apply(""), "");
+    }
+
+    #[test]
+    fn from_lit_lists_known_cases() {
+        let lit: LitStr = parse_quote!("snake_case");
+        assert!(matches!(Case::from_lit(&lit), Ok(Case::Snake)));
+        let lit: LitStr = parse_quote!("Snake");
+        let err = Case::from_lit(&lit).err().unwrap().to_string();
+        assert!(err.starts_with("unknown case 'Snake', expected one of: lowercase, UPPERCASE"));
+    }
+}
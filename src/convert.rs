@@ -0,0 +1,128 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Field converters given with `#[rustifact(with = "...")]`.
+//!
+//! A converter is a function `fn(&T) -> R` where `T` is the field type and `R: ToTokenStream`,
+//! the result being emitted in place of the field. It may also be given as an expression, such as
+//! a closure, either quoted or not. As a proc-macro crate can't export functions,
+//! the built-in converters under `rustifact::convert::` are instead recognised by path and
+//! expanded inline.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::{Expr, Ident, LitStr, Path};
+
+pub(crate) enum Converter {
+    Path(Path),
+    Expr(Expr),
+    Builtin(Builtin),
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Builtin {
+    /// `std::time::Duration` as `Duration::new(secs, nanos)`.
+    Duration,
+    /// `std::time::SystemTime` as a `(secs, nanos)` pair since the unix epoch.
+    SystemTime,
+    /// `RangeInclusive<char>` as `'a'..='z'`.
+    CharRange,
+    /// Any `AsRef<Path>` as a `&'static str`, panicking if not valid UTF-8.
+    Path,
+    /// Any `Display` type as the `&'static str` it formats to.
+    Display,
+}
+
+const BUILTINS: &[(&str, Builtin)] = &[
+    ("duration", Builtin::Duration),
+    ("system_time", Builtin::SystemTime),
+    ("char_range", Builtin::CharRange),
+    ("path", Builtin::Path),
+    ("display", Builtin::Display),
+];
+
+impl Converter {
+    /// The converter following `=` in an attribute, as a string or an expression.
+    pub fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitStr) {
+            Converter::from_lit(&input.parse()?)
+        } else {
+            Ok(Converter::Expr(input.parse()?))
+        }
+    }
+
+    pub fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        let Ok(path) = lit.parse::<Path>() else {
+            return Ok(Converter::Expr(lit.parse()?));
+        };
+        let segs: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+        match segs.as_slice() {
+            [krate, module, name] if krate == "rustifact" && module == "convert" => {
+                match BUILTINS.iter().find(|(n, _)| n == name) {
+                    Some((_, b)) => Ok(Converter::Builtin(*b)),
+                    None => {
+                        let known: Vec<&str> = BUILTINS.iter().map(|(n, _)| *n).collect();
+                        Err(syn::Error::new(
+                            lit.span(),
+                            format!(
+                                "unknown converter 'rustifact::convert::{}', expected one of: {}",
+                                name,
+                                known.join(", ")
+                            ),
+                        ))
+                    }
+                }
+            }
+            _ => Ok(Converter::Path(path)),
+        }
+    }
+
+    /// Build-side expression evaluating to the tokens of `value`, a reference to the field.
+    pub fn value_toks(&self, value: &TokenStream) -> TokenStream {
+        match self {
+            Converter::Path(path) => quote! {
+                rustifact::ToTokenStream::to_tok_stream(&#path(#value))
+            },
+            Converter::Expr(expr) => quote! {
+                rustifact::ToTokenStream::to_tok_stream(&(#expr)(#value))
+            },
+            Converter::Builtin(b) => builtin_toks(*b, value),
+        }
+    }
+}
+
+fn builtin_toks(builtin: Builtin, value: &TokenStream) -> TokenStream {
+    let a = Ident::new("a", Span::call_site());
+    let b = Ident::new("b", Span::call_site());
+    match builtin {
+        Builtin::Duration => quote! {{
+            let d: &::std::time::Duration = #value;
+            let #a = rustifact::ToTokenStream::to_tok_stream(&d.as_secs());
+            let #b = rustifact::ToTokenStream::to_tok_stream(&d.subsec_nanos());
+            rustifact::internal::quote! { ::core::time::Duration::new(##a, ##b) }
+        }},
+        Builtin::SystemTime => quote! {{
+            let t: &::std::time::SystemTime = #value;
+            let d = t
+                .duration_since(::std::time::UNIX_EPOCH)
+                .expect("rustifact: SystemTime before the unix epoch");
+            rustifact::ToTokenStream::to_tok_stream(&(d.as_secs(), d.subsec_nanos()))
+        }},
+        Builtin::CharRange => quote! {{
+            let r: &::std::ops::RangeInclusive<char> = #value;
+            let #a = rustifact::ToTokenStream::to_tok_stream(r.start());
+            let #b = rustifact::ToTokenStream::to_tok_stream(r.end());
+            rustifact::internal::quote! { ##a..=##b }
+        }},
+        Builtin::Path => quote! {{
+            let p: &::std::path::Path = ::std::convert::AsRef::as_ref(#value);
+            let s = p.to_str().expect("rustifact: path is not valid UTF-8");
+            rustifact::ToTokenStream::to_tok_stream(&s)
+        }},
+        Builtin::Display => quote! {
+            rustifact::ToTokenStream::to_tok_stream(&::std::string::ToString::to_string(#value))
+        },
+    }
+}
@@ -0,0 +1,2109 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Build-side code producing the tokens of a single field value.
+//!
+//! Most fields are emitted by their own `ToTokenStream` implementation. Some types are instead
+//! recognised by their syntax, since they either can't implement `ToTokenStream` or need a
+//! particular constructor in the artifact.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{GenericArgument, Ident, LitStr, Path, PathArguments, Type};
+
+use crate::attr::{ContainerAttrs, FieldAttrs};
+
+/// Attributes for the helper items, such as statics, which the derive emits into the artifact, so
+/// artifact crates with strict lint settings still compile without warnings.
+pub(crate) fn helper_attrs() -> TokenStream {
+    quote! {
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals, dead_code, missing_docs, clippy::all, clippy::pedantic)]
+    }
+}
+
+/// `ty` without the invisible groups wrapping types substituted for `$t:ty` in `macro_rules!`
+/// definitions, or redundant parentheses, so macro-generated types are mapped like hand-written
+/// ones.
+pub(crate) fn ungroup(mut ty: &Type) -> &Type {
+    loop {
+        match ty {
+            Type::Group(g) => ty = &g.elem,
+            Type::Paren(p) => ty = &p.elem,
+            _ => return ty,
+        }
+    }
+}
+
+/// The path of `ty` without generic arguments on its last segment, if `ty` is a path type whose
+/// last segment is one of `names`.
+pub(crate) fn path_named(ty: &Type, names: &[&str]) -> Option<Path> {
+    let Type::Path(tp) = ungroup(ty) else {
+        return None;
+    };
+    let last = tp.path.segments.last()?;
+    if !names.iter().any(|n| last.ident == n) {
+        return None;
+    }
+    let mut path = tp.path.clone();
+    path.segments.last_mut().unwrap().arguments = PathArguments::None;
+    Some(path)
+}
+
+/// Adjusts `field_attrs` for the emission of a field of type `ty` into one of type `out_ty`, as
+/// given by `#[OutFields(...)]`: strings into `String` are emitted owned, `Vec`s into
+/// `&'static [T]` as static references, hoisted fields into statics of the referenced type, and
+/// numbers into those of a primitive numeric type unsuffixed.
+pub(crate) fn select_for_out(ty: &Type, out_ty: &Type, field_attrs: &mut FieldAttrs) {
+    if is_numeric_out(out_ty) {
+        field_attrs.unsuffixed = true;
+    }
+    if let (Some(None), Type::Reference(r)) = (&field_attrs.hoist, ungroup(out_ty)) {
+        let referent = match ungroup(&r.elem) {
+            Type::Slice(s) => &*s.elem,
+            referent => referent,
+        };
+        field_attrs.hoist = Some(Some(referent.clone()));
+    }
+    let is_str = match ungroup(ty) {
+        Type::Reference(r) => path_named(&r.elem, &["str"]).is_some(),
+        _ => path_named(ty, &["String"]).is_some(),
+    };
+    if is_str && path_named(out_ty, &["String"]).is_some() {
+        field_attrs.owned = true;
+    }
+    let to_slice =
+        matches!(ungroup(out_ty), Type::Reference(r) if matches!(ungroup(&r.elem), Type::Slice(_)));
+    if to_slice
+        && type_args(ty, &["Vec"]).is_some()
+        && field_attrs.with.is_none()
+        && field_attrs.hoist.is_none()
+        && field_attrs.static_ref.is_none()
+    {
+        field_attrs.static_ref = Some(None);
+    }
+}
+
+/// Whether `ty` is a primitive numeric type, or an array, slice, reference or option of one, whose
+/// literals then need no suffix.
+fn is_numeric_out(ty: &Type) -> bool {
+    match ungroup(ty) {
+        Type::Array(a) => is_numeric_out(&a.elem),
+        Type::Slice(s) => is_numeric_out(&s.elem),
+        Type::Reference(r) => is_numeric_out(&r.elem),
+        ty => match type_args(ty, &["Option"]) {
+            Some(args) => args.len() == 1 && is_numeric_out(args[0]),
+            None => path_named(ty, NUMERIC).is_some(),
+        },
+    }
+}
+
+/// Whether `ty` is `bool`.
+pub(crate) fn is_bool(ty: &Type) -> bool {
+    matches!(ungroup(ty), Type::Path(tp) if tp.qself.is_none() && tp.path.is_ident("bool"))
+}
+
+/// The type arguments of `ty`, if `ty` is a path type whose last segment is one of `names`.
+pub(crate) fn type_args<'a>(ty: &'a Type, names: &[&str]) -> Option<Vec<&'a Type>> {
+    let Type::Path(tp) = ungroup(ty) else {
+        return None;
+    };
+    let last = tp.path.segments.last()?;
+    if !names.iter().any(|n| last.ident == n) {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &last.arguments else {
+        return None;
+    };
+    Some(
+        args.args
+            .iter()
+            .filter_map(|a| match a {
+                GenericArgument::Type(t) => Some(t),
+                _ => None,
+            })
+            .collect(),
+    )
+}
+
+const NONZERO: &[&str] = &[
+    "NonZeroU8",
+    "NonZeroU16",
+    "NonZeroU32",
+    "NonZeroU64",
+    "NonZeroU128",
+    "NonZeroUsize",
+    "NonZeroI8",
+    "NonZeroI16",
+    "NonZeroI32",
+    "NonZeroI64",
+    "NonZeroI128",
+    "NonZeroIsize",
+];
+
+/// `NonZeroU32` and friends (or `NonZero<T>`) as `NonZeroU32::new(x).unwrap()`, or as
+/// `unsafe { NonZeroU32::new_unchecked(x) }` when the field is marked `unchecked`.
+fn nonzero_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    let out_path = if let Some(path) = path_named(ty, NONZERO) {
+        let name = &path.segments.last().unwrap().ident;
+        quote! { ::core::num::#name }
+    } else if let Some(args) = type_args(ty, &["NonZero"]) {
+        let arg = args.first()?;
+        // `NonZero<T>` is stable from 1.79, before which the primitives have their own types.
+        let named = path_named(arg, &["u8", "u16", "u32", "u64", "u128", "usize"])
+            .or_else(|| path_named(arg, &["i8", "i16", "i32", "i64", "i128", "isize"]));
+        match named {
+            Some(prim) if !attrs.supports(79) => {
+                let prim = prim.segments.last().unwrap().ident.to_string();
+                let name = format_ident!("NonZero{}{}", prim[..1].to_uppercase(), &prim[1..]);
+                quote! { ::core::num::#name }
+            }
+            _ => quote! { ::core::num::NonZero::<#arg> },
+        }
+    } else {
+        return None;
+    };
+    let n = Ident::new("n", Span::call_site());
+    let construct = if field_attrs.unchecked {
+        quote! { unsafe { #out_path::new_unchecked(##n) } }
+    } else if attrs.supports(83) {
+        quote! { #out_path::new(##n).unwrap() }
+    } else {
+        // `Option::unwrap` is usable in consts from 1.83.
+        quote! {
+            match #out_path::new(##n) {
+                ::core::option::Option::Some(n) => n,
+                ::core::option::Option::None => ::core::panic!("zero value of a non-zero type"),
+            }
+        }
+    };
+    Some(quote! {{
+        let #n = rustifact::ToTokenStream::to_tok_stream(&(#value).get());
+        rustifact::internal::quote! { #construct }
+    }})
+}
+
+fn addr_toks(value: &TokenStream, parts: &str, n: usize, ty: &str) -> TokenStream {
+    let p: Vec<Ident> = (0..n).map(|i| format_ident!("p{}", i)).collect();
+    let parts = Ident::new(parts, Span::call_site());
+    let ty = Ident::new(ty, Span::call_site());
+    quote! {{
+        let [#(#p),*] = (#value).#parts();
+        #(let #p = rustifact::ToTokenStream::to_tok_stream(&#p);)*
+        rustifact::internal::quote! { ::std::net::#ty::new(#(##p),*) }
+    }}
+}
+
+fn ipv4_toks(value: &TokenStream) -> TokenStream {
+    addr_toks(value, "octets", 4, "Ipv4Addr")
+}
+
+fn ipv6_toks(value: &TokenStream) -> TokenStream {
+    addr_toks(value, "segments", 8, "Ipv6Addr")
+}
+
+/// `Ipv4Addr`, `Ipv6Addr`, `IpAddr` and the socket address types, as const constructor calls.
+fn net_toks(ty: &Type, value: &TokenStream) -> Option<TokenStream> {
+    let name = path_named(
+        ty,
+        &[
+            "Ipv4Addr",
+            "Ipv6Addr",
+            "IpAddr",
+            "SocketAddrV4",
+            "SocketAddrV6",
+            "SocketAddr",
+        ],
+    )?;
+    let name = name.segments.last().unwrap().ident.to_string();
+    let [a, ip, port, f, id] =
+        ["a", "ip", "port", "f", "id"].map(|n| Ident::new(n, Span::call_site()));
+    let v4 = ipv4_toks(&quote! { #a });
+    let v6 = ipv6_toks(&quote! { #a });
+    let sock_v4 = quote! {{
+        let #ip = { let #a = s.ip(); #v4 };
+        let #port = rustifact::ToTokenStream::to_tok_stream(&s.port());
+        rustifact::internal::quote! { ::std::net::SocketAddrV4::new(##ip, ##port) }
+    }};
+    let sock_v6 = quote! {{
+        let #ip = { let #a = s.ip(); #v6 };
+        let #port = rustifact::ToTokenStream::to_tok_stream(&s.port());
+        let #f = rustifact::ToTokenStream::to_tok_stream(&s.flowinfo());
+        let #id = rustifact::ToTokenStream::to_tok_stream(&s.scope_id());
+        rustifact::internal::quote! { ::std::net::SocketAddrV6::new(##ip, ##port, ##f, ##id) }
+    }};
+    let toks = match name.as_str() {
+        "Ipv4Addr" => ipv4_toks(value),
+        "Ipv6Addr" => ipv6_toks(value),
+        "IpAddr" => quote! {
+            match #value {
+                ::std::net::IpAddr::V4(#a) => {
+                    let #a = #v4;
+                    rustifact::internal::quote! { ::std::net::IpAddr::V4(##a) }
+                }
+                ::std::net::IpAddr::V6(#a) => {
+                    let #a = #v6;
+                    rustifact::internal::quote! { ::std::net::IpAddr::V6(##a) }
+                }
+            }
+        },
+        "SocketAddrV4" => quote! {{ let s = #value; #sock_v4 }},
+        "SocketAddrV6" => quote! {{ let s = #value; #sock_v6 }},
+        _ => quote! {
+            match #value {
+                ::std::net::SocketAddr::V4(s) => {
+                    let #a = #sock_v4;
+                    rustifact::internal::quote! { ::std::net::SocketAddr::V4(##a) }
+                }
+                ::std::net::SocketAddr::V6(s) => {
+                    let #a = #sock_v6;
+                    rustifact::internal::quote! { ::std::net::SocketAddr::V6(##a) }
+                }
+            }
+        },
+    };
+    Some(toks)
+}
+
+/// `f32` and `f64` as `f64::from_bits(0x...)`, when the field is marked `float_bits`.
+fn float_bits_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    if !field_attrs.float_bits {
+        return None;
+    }
+    let float = path_named(ty, &["f32", "f64"])?;
+    let bits = Ident::new("bits", Span::call_site());
+    // `from_bits` is usable in consts from 1.83, and `transmute` from 1.56. Newer compilers lint
+    // the transmute, which is only emitted for older ones.
+    let int = if float.is_ident("f32") {
+        quote! { u32 }
+    } else {
+        quote! { u64 }
+    };
+    let construct = if attrs.supports(83) {
+        quote! { #float::from_bits(##bits) }
+    } else {
+        let helper = helper_attrs();
+        quote! {
+            {
+                #helper
+                #[allow(unknown_lints, unnecessary_transmutes)]
+                const BITS: #float = unsafe { ::core::mem::transmute::<#int, #float>(##bits) };
+                BITS
+            }
+        }
+    };
+    Some(quote! {{
+        let #bits = (#value).to_bits();
+        let #bits: rustifact::internal::TokenStream = format!("{:#x}", #bits).parse().unwrap();
+        rustifact::internal::quote! { #construct }
+    }})
+}
+
+/// `usize` and `isize` as literals of the fixed-width type given by `usize_as` on the field or
+/// container, since the pointer width of the build host may not be that of the target. Values out
+/// of the range of the type stop the build.
+fn usize_as_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    let width = field_attrs.usize_as.as_ref().or(attrs.usize_as.as_ref())?;
+    path_named(ty, &["usize", "isize"])?;
+    let width_name = width.to_string();
+    let lit = if field_attrs.unsuffixed || attrs.unsuffixed {
+        quote! { n.to_string().parse::<rustifact::internal::TokenStream>().unwrap() }
+    } else {
+        quote! { rustifact::ToTokenStream::to_tok_stream(&n) }
+    };
+    Some(quote! {{
+        let n = *(#value);
+        let n = match #width::try_from(n) {
+            ::core::result::Result::Ok(n) => n,
+            ::core::result::Result::Err(_) => {
+                ::core::panic!("rustifact: {} doesn't fit in the {} given by usize_as", n, #width_name)
+            }
+        };
+        #lit
+    }})
+}
+
+/// Primitive integers and finite floats as literals without a type suffix, which the out field
+/// type then determines, when the field or container is marked `unsuffixed`.
+fn unsuffixed_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    if !(field_attrs.unsuffixed || attrs.unsuffixed) {
+        return None;
+    }
+    if path_named(ty, &NUMERIC[..12]).is_some() {
+        return Some(quote! {
+            (#value).to_string().parse::<rustifact::internal::TokenStream>().unwrap()
+        });
+    }
+    path_named(ty, &["f32", "f64"])?;
+    Some(quote! {{
+        let f = *(#value);
+        if f.is_finite() {
+            // Debug formatting always includes a decimal point or exponent.
+            format!("{:?}", f).parse::<rustifact::internal::TokenStream>().unwrap()
+        } else {
+            rustifact::ToTokenStream::to_tok_stream(&f)
+        }
+    }})
+}
+
+/// The base in which integers are emitted, given by `radix = "..."`.
+#[derive(Clone, Copy)]
+pub(crate) enum Radix {
+    Hex,
+    Bin,
+    Oct,
+}
+
+impl Radix {
+    pub fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        match lit.value().as_str() {
+            "hex" => Ok(Radix::Hex),
+            "bin" => Ok(Radix::Bin),
+            "oct" => Ok(Radix::Oct),
+            other => Err(syn::Error::new(
+                lit.span(),
+                format!("unknown radix '{}', expected one of: hex, bin, oct", other),
+            )),
+        }
+    }
+}
+
+/// Primitive integers in fields marked `radix = "..."`, as literals such as `0xDEAD_BEEFu32`, with
+/// digits grouped by underscores.
+fn radix_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    let radix = field_attrs.radix?;
+    let name = path_named(ty, &NUMERIC[..12])?.get_ident()?.to_string();
+    let (prefix, group, digits) = match radix {
+        Radix::Hex => ("0x", 4usize, quote! { format!("{:X}", n) }),
+        Radix::Bin => ("0b", 4usize, quote! { format!("{:b}", n) }),
+        Radix::Oct => ("0o", 3usize, quote! { format!("{:o}", n) }),
+    };
+    let split = if name.starts_with('i') {
+        quote! {{
+            let (sign, n) = (if n < 0 { "-" } else { "" }, n.unsigned_abs());
+            (sign, #digits)
+        }}
+    } else {
+        quote! { ("", #digits) }
+    };
+    let suffix = if field_attrs.unsuffixed || attrs.unsuffixed {
+        String::new()
+    } else {
+        name
+    };
+    Some(quote! {{
+        let n = *(#value);
+        let (sign, digits) = #split;
+        let mut lit = format!("{}{}", sign, #prefix);
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % #group == 0 {
+                lit.push('_');
+            }
+            lit.push(c);
+        }
+        lit.push_str(#suffix);
+        lit.parse::<rustifact::internal::TokenStream>().unwrap()
+    }})
+}
+
+/// `String` and `&str` as `String::from("...")`, when the field or container is marked `owned`.
+fn owned_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    if !(field_attrs.owned || attrs.owned) {
+        return None;
+    }
+    let is_str = match ungroup(ty) {
+        Type::Reference(r) => path_named(&r.elem, &["str"]).is_some(),
+        _ => path_named(ty, &["String"]).is_some(),
+    };
+    if !is_str {
+        return None;
+    }
+    let s = Ident::new("s", Span::call_site());
+    Some(quote! {{
+        let #s = rustifact::ToTokenStream::to_tok_stream(&::std::convert::AsRef::<str>::as_ref(#value));
+        rustifact::internal::quote! { ::std::string::String::from(##s) }
+    }})
+}
+
+/// `Box<str>`, `Rc<str>` and `Arc<str>` as a `&'static str`, or, when the field or container is
+/// marked `owned`, as `Box::from("...")` and the like.
+fn str_pointer_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    let pointer = path_named(ty, &["Box", "Rc", "Arc"])?;
+    path_named(type_args(ty, &["Box", "Rc", "Arc"])?.first()?, &["str"])?;
+    let s = Ident::new("s", Span::call_site());
+    let lit = quote! {
+        let #s: &str = &**(#value);
+        let #s = rustifact::ToTokenStream::to_tok_stream(&#s);
+    };
+    if !(field_attrs.owned || attrs.owned) {
+        return Some(quote! {{
+            #lit
+            #s
+        }});
+    }
+    let pointer = match pointer.segments.last().unwrap().ident.to_string().as_str() {
+        "Box" => quote! { ::std::boxed::Box },
+        "Rc" => quote! { ::std::rc::Rc },
+        _ => quote! { ::std::sync::Arc },
+    };
+    Some(quote! {{
+        #lit
+        rustifact::internal::quote! { #pointer::<str>::from(##s) }
+    }})
+}
+
+/// `Cow<str>`, `Cow<[T]>` and `Cow<T>` as the reference they'd borrow, as `&'static str`,
+/// `&'static [T]` and `&'static T`, or as a `Cow::Borrowed` of it when the field is marked `cow`.
+fn cow_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    let borrowed_ty = type_args(ty, &["Cow"])?.first().copied()?;
+    let b = Ident::new("b", Span::call_site());
+    let reference = if path_named(borrowed_ty, &["str"]).is_some() {
+        quote! { rustifact::ToTokenStream::to_tok_stream(&#b) }
+    } else if let Type::Slice(slice) = ungroup(borrowed_ty) {
+        let inner = elem_toks(&slice.elem, &quote! { v }, field_attrs, attrs);
+        let elems = Ident::new("elems", Span::call_site());
+        let seq = seq_toks(&quote! { #b.iter() }, &inner);
+        quote! {{
+            let #elems = #seq;
+            rustifact::internal::quote! { &[##elems] }
+        }}
+    } else {
+        let inner = elem_toks(borrowed_ty, &quote! { #b }, field_attrs, attrs);
+        quote! {{
+            let #b = #inner;
+            rustifact::internal::quote! { &##b }
+        }}
+    };
+    let out = if field_attrs.cow {
+        quote! { ::std::borrow::Cow::Borrowed(##b) }
+    } else {
+        quote! { ##b }
+    };
+    Some(quote! {{
+        let #b: &#borrowed_ty = &**(#value);
+        let #b = #reference;
+        rustifact::internal::quote! { #out }
+    }})
+}
+
+/// `rc::Weak<T>` and `sync::Weak<T>` as a `Weak::new()` of the same path, as references to other
+/// values can't be carried into the artifact.
+fn weak_toks(ty: &Type) -> Option<TokenStream> {
+    let path = path_named(ty, &["Weak"])?;
+    type_args(ty, &["Weak"])?;
+    let colon = &path.leading_colon;
+    let prefix = path.segments.iter().rev().skip(1).rev().map(|s| &s.ident);
+    Some(quote! {
+        rustifact::internal::quote! { #colon #(#prefix::)* Weak::new() }
+    })
+}
+
+/// The atomic integer and bool types of `core::sync::atomic`.
+const ATOMIC: &[&str] = &[
+    "AtomicBool",
+    "AtomicU8",
+    "AtomicU16",
+    "AtomicU32",
+    "AtomicU64",
+    "AtomicUsize",
+    "AtomicI8",
+    "AtomicI16",
+    "AtomicI32",
+    "AtomicI64",
+    "AtomicIsize",
+];
+
+/// `AtomicU32` and friends as `AtomicU32::new(x)`, of the value loaded with `Relaxed` ordering at
+/// emission time.
+fn atomic_toks(ty: &Type, value: &TokenStream) -> Option<TokenStream> {
+    let path = path_named(ty, ATOMIC)?;
+    let name = &path.segments.last().unwrap().ident;
+    let n = Ident::new("n", Span::call_site());
+    Some(quote! {{
+        let #n = (#value).load(::core::sync::atomic::Ordering::Relaxed);
+        let #n = rustifact::ToTokenStream::to_tok_stream(&#n);
+        rustifact::internal::quote! { ::core::sync::atomic::#name::new(##n) }
+    }})
+}
+
+/// `Cell<T>`, `RefCell<T>`, `Mutex<T>` and `RwLock<T>` as `Cell::new(x)` and the like, of the
+/// value read at emission time. `Cell` requires `T: Copy`, and locks poisoned at emission time stop
+/// the build.
+fn interior_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    let names = ["Cell", "RefCell", "Mutex", "RwLock"];
+    let path = path_named(ty, &names)?;
+    let inner_ty = type_args(ty, &names)?.first().copied()?;
+    let x = Ident::new("x", Span::call_site());
+    let guard = quote! { &*#x };
+    let (read, place, wrapper) = match path.segments.last().unwrap().ident.to_string().as_str() {
+        "Cell" => (
+            quote! { (#value).get() },
+            quote! { &#x },
+            quote! { ::core::cell::Cell },
+        ),
+        "RefCell" => (
+            quote! { (#value).borrow() },
+            guard,
+            quote! { ::core::cell::RefCell },
+        ),
+        "Mutex" => (
+            quote! { (#value).lock().expect("rustifact: the mutex is poisoned") },
+            guard,
+            quote! { ::std::sync::Mutex },
+        ),
+        _ => (
+            quote! { (#value).read().expect("rustifact: the lock is poisoned") },
+            guard,
+            quote! { ::std::sync::RwLock },
+        ),
+    };
+    let inner = elem_toks(inner_ty, &place, field_attrs, attrs);
+    Some(quote! {{
+        let #x = #read;
+        let #x = #inner;
+        rustifact::internal::quote! { #wrapper::new(##x) }
+    }})
+}
+
+/// `OnceLock<T>` and `OnceCell<T>` as `OnceLock::new()`, left to be initialized at runtime, or
+/// when the field is marked `seed`, as a `LazyLock` initialized to the value set at emission time.
+fn once_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    let path = path_named(ty, &["OnceLock", "OnceCell"])?;
+    let inner_ty = type_args(ty, &["OnceLock", "OnceCell"])?.first().copied()?;
+    if !field_attrs.seed {
+        let cell = if path.segments.last().unwrap().ident == "OnceLock" {
+            quote! { ::std::sync::OnceLock }
+        } else {
+            quote! { ::core::cell::OnceCell }
+        };
+        return Some(quote! { rustifact::internal::quote! { #cell::new() } });
+    }
+    let x = Ident::new("x", Span::call_site());
+    let inner = elem_toks(inner_ty, &quote! { #x }, field_attrs, attrs);
+    let lazy = lazy_path(attrs);
+    Some(quote! {{
+        let #x = (#value)
+            .get()
+            .expect("rustifact: a field marked seed wasn't set before emission");
+        let #x = #inner;
+        rustifact::internal::quote! { #lazy::new(|| ##x) }
+    }})
+}
+
+/// `Uuid`, with the `uuid` feature, as the `const fn` `Uuid::from_u128(0x...)`.
+fn uuid_toks(ty: &Type, value: &TokenStream) -> Option<TokenStream> {
+    if !cfg!(feature = "uuid") {
+        return None;
+    }
+    path_named(ty, &["Uuid"])?;
+    let n = Ident::new("n", Span::call_site());
+    Some(quote! {{
+        let #n = format!("{:#034x}u128", (#value).as_u128())
+            .parse::<rustifact::internal::TokenStream>()
+            .unwrap();
+        rustifact::internal::quote! { ::uuid::Uuid::from_u128(##n) }
+    }})
+}
+
+/// chrono's `NaiveDate` and `DateTime<Utc>`, with the `chrono` feature, and time's
+/// `OffsetDateTime`, with the `time` feature, as their `const fn` constructors.
+fn datetime_toks(ty: &Type, value: &TokenStream) -> Option<TokenStream> {
+    let name = path_named(ty, &["NaiveDate", "DateTime", "OffsetDateTime"])?;
+    let name = name.segments.last().unwrap().ident.to_string();
+    let [a, b, c] = ["a", "b", "c"].map(|n| Ident::new(n, Span::call_site()));
+    let part = |e: TokenStream| quote! { rustifact::ToTokenStream::to_tok_stream(&#e) };
+    let (parts, expr) = match name.as_str() {
+        "NaiveDate" if cfg!(feature = "chrono") => (
+            vec![
+                part(quote! { ::chrono::Datelike::year(d) }),
+                part(quote! { ::chrono::Datelike::month(d) }),
+                part(quote! { ::chrono::Datelike::day(d) }),
+            ],
+            quote! {
+                match ::chrono::NaiveDate::from_ymd_opt(##a, ##b, ##c) {
+                    ::core::option::Option::Some(d) => d,
+                    ::core::option::Option::None => ::core::panic!("invalid date"),
+                }
+            },
+        ),
+        "DateTime"
+            if cfg!(feature = "chrono")
+                && type_args(ty, &["DateTime"])?
+                    .first()
+                    .is_some_and(|tz| path_named(tz, &["Utc"]).is_some()) =>
+        {
+            (
+                vec![
+                    part(quote! { d.timestamp() }),
+                    part(quote! { d.timestamp_subsec_nanos() }),
+                ],
+                quote! {
+                    match ::chrono::DateTime::from_timestamp(##a, ##b) {
+                        ::core::option::Option::Some(d) => d,
+                        ::core::option::Option::None => ::core::panic!("invalid timestamp"),
+                    }
+                },
+            )
+        }
+        "OffsetDateTime" if cfg!(feature = "time") => (
+            vec![
+                part(quote! { d.unix_timestamp_nanos() }),
+                part(quote! { d.offset().whole_seconds() }),
+            ],
+            quote! {
+                match ::time::OffsetDateTime::from_unix_timestamp_nanos(##a) {
+                    ::core::result::Result::Ok(d) => match ::time::UtcOffset::from_whole_seconds(##b) {
+                        ::core::result::Result::Ok(o) => d.to_offset(o),
+                        ::core::result::Result::Err(_) => ::core::panic!("invalid offset"),
+                    },
+                    ::core::result::Result::Err(_) => ::core::panic!("invalid timestamp"),
+                }
+            },
+        ),
+        _ => return None,
+    };
+    let idents = [&a, &b, &c].into_iter().take(parts.len());
+    Some(quote! {{
+        let d = #value;
+        #(let #idents = #parts;)*
+        rustifact::internal::quote! { #expr }
+    }})
+}
+
+/// Types generated by `bitflags!`, in fields marked `bitflags`, as `T::from_bits_retain(0b...)`,
+/// which keeps unknown bits.
+fn bitflags_toks(ty: &Type, value: &TokenStream, field_attrs: &FieldAttrs) -> Option<TokenStream> {
+    if !field_attrs.bitflags {
+        return None;
+    }
+    let Type::Path(tp) = ungroup(ty) else {
+        return None;
+    };
+    if !tp.path.segments.last()?.arguments.is_none() {
+        return None;
+    }
+    let bits = Ident::new("bits", Span::call_site());
+    Some(quote! {{
+        let #bits = format!("{:#b}", (#value).bits())
+            .parse::<rustifact::internal::TokenStream>()
+            .unwrap();
+        rustifact::internal::quote! { #tp::from_bits_retain(##bits) }
+    }})
+}
+
+/// String types of other crates, as the `&'static str` they hold, like `String`. `SmolStr` and
+/// `CompactString` are recognised with the `smol_str` and `compact_str` features, and any other
+/// type implementing `AsRef<str>`, without generic arguments, in fields marked `string_like`.
+fn string_like_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    let Type::Path(tp) = ungroup(ty) else {
+        return None;
+    };
+    let last = tp.path.segments.last()?;
+    let known = (cfg!(feature = "smol_str") && last.ident == "SmolStr")
+        || (cfg!(feature = "compact_str") && last.ident == "CompactString");
+    if !(known || (field_attrs.string_like && last.arguments.is_none())) {
+        return None;
+    }
+    let s = Ident::new("s", Span::call_site());
+    let out = if field_attrs.owned || attrs.owned {
+        quote! { ::std::string::String::from(##s) }
+    } else {
+        quote! { ##s }
+    };
+    Some(quote! {{
+        let #s = rustifact::ToTokenStream::to_tok_stream(&::std::convert::AsRef::<str>::as_ref(#value));
+        rustifact::internal::quote! { #out }
+    }})
+}
+
+fn bindgen_value_toks(ty: &Type, value: &TokenStream) -> Option<TokenStream> {
+    if let Some(path) = path_named(ty, &["__BindgenBitfieldUnit"]) {
+        let storage = Ident::new("storage", Span::call_site());
+        return Some(quote! {{
+            let #storage = (&(#value).storage).to_tok_stream();
+            rustifact::internal::quote! { #path::new(##storage) }
+        }});
+    }
+    if let Some(path) = path_named(ty, &["__IncompleteArrayField", "__BindgenUnionField"]) {
+        return Some(quote! { rustifact::internal::quote! { #path::new() } });
+    }
+    None
+}
+
+/// Build-side expression evaluating to the comma-separated tokens of the elements of `value`,
+/// where `inner` gives the tokens of an element `v`.
+fn seq_toks(value: &TokenStream, inner: &TokenStream) -> TokenStream {
+    let v = Ident::new("v", Span::call_site());
+    quote! {{
+        let mut elems = rustifact::internal::TokenStream::new();
+        for #v in #value {
+            let #v = #inner;
+            elems.extend(rustifact::internal::quote! { ##v, });
+        }
+        elems
+    }}
+}
+
+/// Tuples, where some position either has a converter or needs special handling.
+fn tuple_toks(
+    tuple: &syn::TypeTuple,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    let mut any_mapped = false;
+    let mut init = TokenStream::new();
+    let mut elems = TokenStream::new();
+    for (i, elem) in tuple.elems.iter().enumerate() {
+        let index = syn::Index::from(i);
+        let place = quote! { &(#value).#index };
+        let with = field_attrs.with_positions.iter().find(|(n, _)| *n == i);
+        let toks = match with {
+            Some((_, with)) => Some(with.value_toks(&place)),
+            None => mapped_toks(elem, &place, field_attrs, attrs),
+        };
+        any_mapped |= toks.is_some();
+        let toks = toks.unwrap_or_else(|| quote! { (#place).to_tok_stream() });
+        let t = format_ident!("t{}", i);
+        init.extend(quote! { let #t = #toks; });
+        elems.extend(quote! { ##t, });
+    }
+    if !any_mapped {
+        return None;
+    }
+    Some(quote! {{
+        #init
+        rustifact::internal::quote! { (#elems) }
+    }})
+}
+
+/// `BTreeMap<K, V>` as a `&'static [(K, V)]` in key order, when the field is marked `slice` or
+/// `lookup`, or `sparse` to omit entries with the default value. `HashMap<K, V>` fields marked
+/// `lookup` are likewise emitted in key order.
+fn map_slice_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    if !(field_attrs.slice || field_attrs.sparse || field_attrs.lookup) {
+        return None;
+    }
+    let (args, value) = match type_args(ty, &["HashMap"]) {
+        Some(args) if field_attrs.lookup => (
+            args,
+            quote! {{
+                let mut items: Vec<_> = (#value).iter().collect();
+                items.sort_by(|a, b| a.0.cmp(b.0));
+                items
+            }},
+        ),
+        _ => (type_args(ty, &["BTreeMap"])?, value.clone()),
+    };
+    let [key_ty, value_ty, ..] = args.as_slice() else {
+        return None;
+    };
+    let (k, v) = (
+        Ident::new("k", Span::call_site()),
+        Ident::new("v", Span::call_site()),
+    );
+    let key = elem_toks(key_ty, &quote! { v.0 }, field_attrs, attrs);
+    let val = elem_toks(value_ty, &quote! { v.1 }, field_attrs, attrs);
+    let inner = quote! {{
+        let #k = #key;
+        let #v = #val;
+        rustifact::internal::quote! { (##k, ##v) }
+    }};
+    let entries = if field_attrs.sparse {
+        quote! {
+            (#value).iter().filter(|v| v.1 != &<#value_ty as ::core::default::Default>::default())
+        }
+    } else {
+        value.clone()
+    };
+    let elems = Ident::new("elems", Span::call_site());
+    let seq = seq_toks(&entries, &inner);
+    Some(quote! {{
+        let #elems = #seq;
+        rustifact::internal::quote! { &[##elems] }
+    }})
+}
+
+/// A transition table, when the field is marked `state_machine`, as a closure coercing to a
+/// `fn(S, I) -> Option<T>` of nested matches, first on the state then on the input. The table is
+/// either a `Vec<Vec<T>>` indexed by `usize` state then input, or a `HashMap<(S, I), T>` or
+/// `BTreeMap<(S, I), T>`, whose states and inputs are emitted as patterns in key order.
+fn state_machine_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    if !field_attrs.state_machine {
+        return None;
+    }
+    let idents = ["s", "i", "t", "inputs", "states"].map(|n| Ident::new(n, Span::call_site()));
+    let [s, i, t, inputs, states] = &idents;
+    let arms = if let Some(row_ty) = seq_elem(ty) {
+        let target = elem_toks(seq_elem(row_ty)?, &quote! { v }, field_attrs, attrs);
+        quote! {
+            for (#s, row) in (#value).iter().enumerate() {
+                // States without transitions fall to the final arm.
+                if row.is_empty() {
+                    continue;
+                }
+                let mut #inputs = rustifact::internal::TokenStream::new();
+                for (#i, v) in row.iter().enumerate() {
+                    let #t = #target;
+                    #inputs.extend(rustifact::internal::quote! {
+                        ##i => ::core::option::Option::Some(##t),
+                    });
+                }
+                #states.extend(rustifact::internal::quote! {
+                    ##s => match input { ##inputs _ => ::core::option::Option::None },
+                });
+            }
+        }
+    } else {
+        let args = type_args(ty, &["HashMap", "BTreeMap"])?;
+        let [key_ty, target_ty, ..] = args.as_slice() else {
+            return None;
+        };
+        let Type::Tuple(key) = ungroup(key_ty) else {
+            return None;
+        };
+        let [state_ty, input_ty] = [key.elems.first()?, key.elems.get(1)?];
+        let state = elem_toks(state_ty, &quote! { v }, field_attrs, attrs);
+        let input = elem_toks(input_ty, &quote! { (v.0).1 }, field_attrs, attrs);
+        let target = elem_toks(target_ty, &quote! { v.1 }, field_attrs, attrs);
+        quote! {
+            let mut items: Vec<_> = (#value).iter().collect();
+            items.sort_by(|a, b| a.0.cmp(b.0));
+            let mut k = 0;
+            while k < items.len() {
+                let from = &(items[k].0).0;
+                let mut #inputs = rustifact::internal::TokenStream::new();
+                while k < items.len() && &(items[k].0).0 == from {
+                    let v = items[k];
+                    let #i = #input;
+                    let #t = #target;
+                    #inputs.extend(rustifact::internal::quote! {
+                        ##i => ::core::option::Option::Some(##t),
+                    });
+                    k += 1;
+                }
+                let v = from;
+                let #s = #state;
+                #states.extend(rustifact::internal::quote! {
+                    ##s => match input { ##inputs _ => ::core::option::Option::None },
+                });
+            }
+        }
+    };
+    Some(quote! {{
+        let mut #states = rustifact::internal::TokenStream::new();
+        #arms
+        rustifact::internal::quote! {
+            |state, input| match state { ##states _ => ::core::option::Option::None }
+        }
+    }})
+}
+
+/// `HashMap<K, Vec<V>>` and `BTreeMap<K, Vec<V>>`, when the field is marked `multimap`, as a
+/// `&'static [(K, &'static [V])]` sorted by key.
+fn multimap_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    if !field_attrs.multimap {
+        return None;
+    }
+    let args = type_args(ty, &["HashMap", "BTreeMap"])?;
+    let [key_ty, values_ty, ..] = args.as_slice() else {
+        return None;
+    };
+    let value_ty = seq_elem(values_ty)?;
+    let (k, vs) = (
+        Ident::new("k", Span::call_site()),
+        Ident::new("vs", Span::call_site()),
+    );
+    let key = elem_toks(key_ty, &quote! { entry.0 }, field_attrs, attrs);
+    let val = elem_toks(value_ty, &quote! { v }, field_attrs, attrs);
+    let values = seq_toks(&quote! { entry.1.iter() }, &val);
+    let inner = quote! {{
+        let entry = v;
+        let #k = #key;
+        let #vs = #values;
+        rustifact::internal::quote! { (##k, &[##vs]) }
+    }};
+    let elems = Ident::new("elems", Span::call_site());
+    let seq = seq_toks(&quote! { items }, &inner);
+    Some(quote! {{
+        let mut items: Vec<_> = (#value).iter().collect();
+        items.sort_by(|a, b| a.0.cmp(b.0));
+        let #elems = #seq;
+        rustifact::internal::quote! { &[##elems] }
+    }})
+}
+
+/// A `HashMap<K, V>` or `BTreeMap<K, V>` with string keys as the nodes of a prefix trie over the
+/// bytes of the keys, each a `(&'static [(u8, u32)], Option<V>)` of edges sorted by byte, with the
+/// index of the node they lead to, and the value of a key ending there. The root is the first.
+fn trie_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    if !field_attrs.trie {
+        return None;
+    }
+    let args = type_args(ty, &["HashMap", "BTreeMap"])?;
+    let [_, value_ty, ..] = args.as_slice() else {
+        return None;
+    };
+    let (b, c, edges, val) = (
+        Ident::new("b", Span::call_site()),
+        Ident::new("c", Span::call_site()),
+        Ident::new("edges", Span::call_site()),
+        Ident::new("val", Span::call_site()),
+    );
+    let elem = elem_toks(value_ty, &quote! { v }, field_attrs, attrs);
+    let inner = quote! {{
+        let (#b, #c) = v;
+        rustifact::internal::quote! { (##b, ##c) }
+    }};
+    let edge_seq = seq_toks(&quote! { node.0.iter().copied() }, &inner);
+    let node = quote! {{
+        let node = v;
+        let #edges = #edge_seq;
+        let #val = match node.1 {
+            ::core::option::Option::Some(v) => {
+                let #val = #elem;
+                rustifact::internal::quote! { ::core::option::Option::Some(##val) }
+            }
+            ::core::option::Option::None => {
+                rustifact::internal::quote! { ::core::option::Option::None }
+            }
+        };
+        rustifact::internal::quote! { (&[##edges], ##val) }
+    }};
+    let nodes = Ident::new("nodes", Span::call_site());
+    let seq = seq_toks(&quote! { nodes.iter() }, &node);
+    Some(quote! {{
+        let mut items: Vec<_> = (#value).iter().collect();
+        items.sort_by(|a, b| ::core::convert::AsRef::<str>::as_ref(a.0)
+            .cmp(::core::convert::AsRef::<str>::as_ref(b.0)));
+        let mut nodes: Vec<(Vec<(u8, u32)>, ::core::option::Option<_>)> =
+            vec![(Vec::new(), ::core::option::Option::None)];
+        for (k, v) in items {
+            let mut n = 0;
+            for b in ::core::convert::AsRef::<str>::as_ref(k).bytes() {
+                n = match nodes[n].0.iter().find(|e| e.0 == b) {
+                    ::core::option::Option::Some(e) => e.1 as usize,
+                    ::core::option::Option::None => {
+                        let c = nodes.len();
+                        nodes.push((Vec::new(), ::core::option::Option::None));
+                        nodes[n].0.push((b, c as u32));
+                        c
+                    }
+                };
+            }
+            nodes[n].1 = ::core::option::Option::Some(v);
+        }
+        let #nodes = #seq;
+        rustifact::internal::quote! { &[##nodes] }
+    }})
+}
+
+/// `VecDeque<T>` and `LinkedList<T>` as a `&'static [T]` in order, or sorted when the field is marked
+/// `sorted`, and `BinaryHeap<T>`, whose iteration order is unspecified, always sorted.
+fn list_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    let args = type_args(ty, &["VecDeque", "LinkedList", "BinaryHeap"])?;
+    let inner = elem_toks(args.first()?, &quote! { v }, field_attrs, attrs);
+    let items = if field_attrs.sorted || path_named(ty, &["BinaryHeap"]).is_some() {
+        quote! {
+            let mut items: Vec<_> = (#value).iter().collect();
+            items.sort();
+        }
+    } else {
+        quote! { let items = (#value).iter(); }
+    };
+    let elems = Ident::new("elems", Span::call_site());
+    let seq = seq_toks(&quote! { items }, &inner);
+    Some(quote! {{
+        #items
+        let #elems = #seq;
+        rustifact::internal::quote! { &[##elems] }
+    }})
+}
+
+/// `HashSet<T>` and `BTreeSet<T>` as a sorted `&'static [T]`, so the artifact can test for
+/// membership with `binary_search`, and so the output doesn't depend on hash ordering.
+fn set_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    let args = type_args(ty, &["HashSet", "BTreeSet"])?;
+    let inner = elem_toks(args.first()?, &quote! { v }, field_attrs, attrs);
+    let elems = Ident::new("elems", Span::call_site());
+    let seq = seq_toks(&quote! { items }, &inner);
+    Some(quote! {{
+        let mut items: Vec<_> = (#value).iter().collect();
+        items.sort();
+        items.dedup();
+        let #elems = #seq;
+        rustifact::internal::quote! { &[##elems] }
+    }})
+}
+
+/// `IndexMap<K, V>` and `IndexSet<T>`, with the `indexmap` feature, as a `&'static [(K, V)]` or
+/// `&'static [T]` in insertion order, or in key order when the field is marked `sorted`.
+fn index_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    if !cfg!(feature = "indexmap") {
+        return None;
+    }
+    let args = type_args(ty, &["IndexMap", "IndexSet"])?;
+    let inner = match args.as_slice() {
+        [key_ty, value_ty, ..] if path_named(ty, &["IndexMap"]).is_some() => {
+            let (k, v) = (
+                Ident::new("k", Span::call_site()),
+                Ident::new("v", Span::call_site()),
+            );
+            let key = elem_toks(key_ty, &quote! { v.0 }, field_attrs, attrs);
+            let val = elem_toks(value_ty, &quote! { v.1 }, field_attrs, attrs);
+            quote! {{
+                let #k = #key;
+                let #v = #val;
+                rustifact::internal::quote! { (##k, ##v) }
+            }}
+        }
+        [elem_ty, ..] => elem_toks(elem_ty, &quote! { v }, field_attrs, attrs),
+        [] => return None,
+    };
+    let items = match (field_attrs.sorted, path_named(ty, &["IndexMap"]).is_some()) {
+        (false, _) => quote! { let items = (#value).iter(); },
+        (true, true) => quote! {
+            let mut items: Vec<_> = (#value).iter().collect();
+            items.sort_by(|a, b| a.0.cmp(b.0));
+        },
+        (true, false) => quote! {
+            let mut items: Vec<_> = (#value).iter().collect();
+            items.sort();
+        },
+    };
+    let elems = Ident::new("elems", Span::call_site());
+    let seq = seq_toks(&quote! { items }, &inner);
+    Some(quote! {{
+        #items
+        let #elems = #seq;
+        rustifact::internal::quote! { &[##elems] }
+    }})
+}
+
+/// `SmallVec<[T; N]>` and `ArrayVec<T, N>`, with the `smallvec` and `arrayvec` features, as a
+/// `&'static [T]`, or as an array `[T; len]` when the field is marked `array`.
+fn inline_vec_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    let elem = if cfg!(feature = "smallvec") && path_named(ty, &["SmallVec"]).is_some() {
+        match ungroup(type_args(ty, &["SmallVec"])?.first()?) {
+            Type::Array(a) => &*a.elem,
+            _ => return None,
+        }
+    } else if cfg!(feature = "arrayvec") && path_named(ty, &["ArrayVec"]).is_some() {
+        type_args(ty, &["ArrayVec"])?.first().copied()?
+    } else {
+        return None;
+    };
+    let inner = elem_toks(elem, &quote! { v }, field_attrs, attrs);
+    let elems = Ident::new("elems", Span::call_site());
+    let seq = seq_toks(&quote! { (#value).iter() }, &inner);
+    let out = if field_attrs.array {
+        quote! { [##elems] }
+    } else {
+        quote! { &[##elems] }
+    };
+    Some(quote! {{
+        let #elems = #seq;
+        rustifact::internal::quote! { #out }
+    }})
+}
+
+/// Build-side expression evaluating to the tokens of `value`, a reference to a field of type `ty`.
+///
+/// `field` names the field within the type, as in `levels`, or `High_0` for the first field of the
+/// variant `High`, naming its static if hoisted.
+pub(crate) fn value_toks(
+    ty: &Type,
+    value: TokenStream,
+    field: &str,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> TokenStream {
+    let inner_ty = match type_args(ty, &["Option", "Result"]) {
+        Some(args) if field_attrs.unwrap_or_default || field_attrs.expect.is_some() => args[0],
+        _ => return unwrapped_toks(ty, value, field, field_attrs, attrs),
+    };
+    let ok = if path_named(ty, &["Result"]).is_some() {
+        quote! { (#value).as_ref().ok() }
+    } else {
+        quote! { (#value).as_ref() }
+    };
+    let unwrapped = Ident::new("unwrapped", Span::call_site());
+    let unwrap = match &field_attrs.expect {
+        Some(msg) => quote! { let #unwrapped = #ok.expect(#msg); },
+        None => quote! {
+            let default = <#inner_ty as ::core::default::Default>::default();
+            let #unwrapped = #ok.unwrap_or(&default);
+        },
+    };
+    let toks = unwrapped_toks(inner_ty, quote! { #unwrapped }, field, field_attrs, attrs);
+    quote! {{
+        #unwrap
+        #toks
+    }}
+}
+
+/// As `value_toks`, for a value already unwrapped by `unwrap_or_default` or `expect`.
+fn unwrapped_toks(
+    ty: &Type,
+    value: TokenStream,
+    field: &str,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> TokenStream {
+    let source = match order_toks(field_attrs) {
+        Some(order) => {
+            let ordered = Ident::new("ordered", Span::call_site());
+            let check = len_check_toks(&quote! { &#ordered }, field, field_attrs);
+            let source = source_toks(ty, quote! { &#ordered }, field, field_attrs, attrs);
+            quote! {{
+                let mut #ordered = ::core::clone::Clone::clone(#value);
+                #order
+                #check
+                #source
+            }}
+        }
+        None => {
+            let check = len_check_toks(&value, field, field_attrs);
+            let source = source_toks(ty, value, field, field_attrs, attrs);
+            quote! {{
+                #check
+                #source
+            }}
+        }
+    };
+    match &field_attrs.runtime_init {
+        // The value is the source for a `LazyLock`, initialized on first access at runtime.
+        Some(init) => {
+            let source_name = Ident::new("source", Span::call_site());
+            let lazy = lazy_path(attrs);
+            quote! {{
+                let #source_name = #source;
+                rustifact::internal::quote! {
+                    #lazy::new(|| #init(##source_name))
+                }
+            }}
+        }
+        None => source,
+    }
+}
+
+/// The type of lazily initialized values, `LazyLock` from 1.80, or once_cell's `Lazy` before.
+pub(crate) fn lazy_path(attrs: &ContainerAttrs) -> TokenStream {
+    if attrs.supports(80) {
+        quote! { ::std::sync::LazyLock }
+    } else {
+        quote! { ::once_cell::sync::Lazy }
+    }
+}
+
+/// Build-side statements stopping the build when the collection `value` has fewer elements than
+/// `min_len` or more than `max_len`.
+fn len_check_toks(value: &TokenStream, field: &str, field_attrs: &FieldAttrs) -> TokenStream {
+    if field_attrs.min_len.is_none() && field_attrs.max_len.is_none() {
+        return TokenStream::new();
+    }
+    let len = quote! { ::core::iter::IntoIterator::into_iter(#value).count() };
+    let min = field_attrs.min_len.map(|min| {
+        quote! {
+            if len < #min {
+                ::core::panic!(
+                    "rustifact: the field `{}` has {} elements, fewer than min_len = {}",
+                    #field,
+                    len,
+                    #min
+                );
+            }
+        }
+    });
+    let max = field_attrs.max_len.map(|max| {
+        quote! {
+            if len > #max {
+                ::core::panic!(
+                    "rustifact: the field `{}` has {} elements, more than max_len = {}",
+                    #field,
+                    len,
+                    #max
+                );
+            }
+        }
+    });
+    quote! {
+        let len = #len;
+        #min
+        #max
+    }
+}
+
+/// Build-side statements rearranging `ordered`, a clone of the collection of the field, when it's
+/// marked `sort_by` or `dedup`. The expression of `sort_by` is either a key of each `item`, or the
+/// path of a comparator `fn(&T, &T) -> Ordering`. Deduplication keeps the first of the elements
+/// sharing a key, or equal elements if no key is given.
+fn order_toks(field_attrs: &FieldAttrs) -> Option<TokenStream> {
+    if field_attrs.sort_by.is_none() && field_attrs.dedup.is_none() {
+        return None;
+    }
+    let ordered = Ident::new("ordered", Span::call_site());
+    let item = Ident::new("item", Span::call_site());
+    let sort = match &field_attrs.sort_by {
+        Some(syn::Expr::Path(p)) if p.path.is_ident("item") => quote! { #ordered.sort(); },
+        Some(syn::Expr::Path(p)) => quote! { #ordered.sort_by(#p); },
+        Some(key) => {
+            quote! { #ordered.sort_by_key(|#item| ::core::clone::Clone::clone(&#key)); }
+        }
+        None => TokenStream::new(),
+    };
+    let dedup = match &field_attrs.dedup {
+        Some(key) => {
+            let key = match key {
+                Some(key) => quote! { ::core::clone::Clone::clone(&#key) },
+                None => quote! { ::core::clone::Clone::clone(#item) },
+            };
+            quote! {
+                let mut seen = ::std::collections::HashSet::new();
+                #ordered.retain(|#item| seen.insert(#key));
+            }
+        }
+        None => TokenStream::new(),
+    };
+    Some(quote! { #sort #dedup })
+}
+
+/// As `value_toks`, without any runtime initialization.
+fn source_toks(
+    ty: &Type,
+    value: TokenStream,
+    field: &str,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> TokenStream {
+    if field_attrs.context {
+        let toks = Ident::new("toks", Span::call_site());
+        return quote! {{
+            let mut #toks = rustifact::internal::TokenStream::new();
+            (#value).to_toks_with(__rustifact_ctx, &mut #toks);
+            #toks
+        }};
+    }
+    if let Some(path) = &field_attrs.with_context {
+        return quote! {{
+            let toks: rustifact::internal::TokenStream = #path(#value, __rustifact_ctx);
+            toks
+        }};
+    }
+    if let Some(hoist) = &field_attrs.hoist {
+        let static_ty = match (hoist, &field_attrs.with, type_args(ty, &["Vec"])) {
+            (Some(static_ty), _, _) => Some(static_ty.clone()),
+            (None, Some(_), _) => None,
+            (None, None, Some(args)) => static_type(args[0]),
+            (None, None, None) => static_type(ty),
+        };
+        let Some(static_ty) = static_ty else {
+            let msg = "the type of the hoisted static isn't known, so give it with `hoist = \"T\"`";
+            return syn::Error::new_spanned(ty, msg).to_compile_error();
+        };
+        return hoist_toks(ty, &value, &static_ty, field, field_attrs, attrs);
+    }
+    if let Some(static_ref) = &field_attrs.static_ref {
+        let value_name = Ident::new("VALUE", Span::call_site());
+        let static_ty = static_ref.as_ref().map(|t| (t, &value_name));
+        return static_ref_toks(ty, &value, static_ty, field_attrs, attrs);
+    }
+    if field_attrs.iter {
+        return iter_toks(&value);
+    }
+    if field_attrs.collection {
+        return collection_toks(&value, field_attrs);
+    }
+    if field_attrs.runs {
+        return runs_toks(&value);
+    }
+    if field_attrs.sidecar {
+        return sidecar_toks(ty, &value, field);
+    }
+    if let Some(size) = field_attrs.chunk {
+        return chunk_toks(ty, &value, size, field_attrs, attrs);
+    }
+    if let Some(toks) = fixed_toks(ty, &value, field, field_attrs, attrs) {
+        return toks;
+    }
+    match &field_attrs.with {
+        Some(with) => with.value_toks(&value),
+        None => elem_toks(ty, &value, field_attrs, attrs),
+    }
+}
+
+/// `Box<dyn Trait>`, as the tokens given by the field's `dyn_with` function, since trait objects
+/// can't be reconstructed generically.
+fn dyn_toks(ty: &Type, value: &TokenStream, field_attrs: &FieldAttrs) -> TokenStream {
+    match &field_attrs.dyn_with {
+        Some(path) => quote! {{
+            let toks: rustifact::internal::TokenStream = #path(&**(#value));
+            toks
+        }},
+        None => syn::Error::new_spanned(
+            ty,
+            "fields of boxed trait objects need #[rustifact(dyn_with = \"...\")] to be emitted",
+        )
+        .to_compile_error(),
+    }
+}
+
+/// Function pointers, as the path given by the field's `fn_path`, or by calling its `fn_path_of`
+/// on the pointer, since the function a pointer refers to can't be recovered generically.
+fn fn_toks(ty: &Type, value: &TokenStream, field_attrs: &FieldAttrs) -> TokenStream {
+    match (&field_attrs.fn_path, &field_attrs.fn_path_of) {
+        (Some(path), None) => quote! { rustifact::internal::quote! { #path } },
+        (None, Some(path_of)) => quote! {{
+            let path: &str = #path_of(#value);
+            path.parse::<rustifact::internal::TokenStream>()
+                .unwrap_or_else(|_| ::core::panic!("rustifact: invalid function path {:?}", path))
+        }},
+        (Some(_), Some(_)) => {
+            let msg = "only one of `fn_path` and `fn_path_of` may be given";
+            syn::Error::new_spanned(ty, msg).to_compile_error()
+        }
+        (None, None) => syn::Error::new_spanned(
+            ty,
+            "function pointer fields need #[rustifact(fn_path = \"...\")] or \
+             #[rustifact(fn_path_of = \"...\")] to be emitted",
+        )
+        .to_compile_error(),
+    }
+}
+
+/// The element type of a `Vec`, array or slice type.
+fn seq_elem(ty: &Type) -> Option<&Type> {
+    match type_args(ty, &["Vec"]) {
+        Some(args) => args.first().copied(),
+        None => match ungroup(ty) {
+            Type::Array(a) => Some(&a.elem),
+            Type::Reference(r) => match ungroup(&r.elem) {
+                Type::Slice(s) => Some(&s.elem),
+                _ => None,
+            },
+            _ => None,
+        },
+    }
+}
+
+/// The primitive numeric types.
+const NUMERIC: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize", "f32",
+    "f64",
+];
+
+/// The primitive numeric element type of a field marked `sidecar`.
+pub(crate) fn sidecar_elem(ty: &Type) -> Option<Path> {
+    path_named(seq_elem(ty)?, NUMERIC)
+}
+
+/// A collection of numbers marked `sidecar`, written in little-endian byte order to a file in
+/// `OUT_DIR`, named after `field`, and emitted as an `include_bytes!` of it.
+fn sidecar_toks(ty: &Type, value: &TokenStream, field: &str) -> TokenStream {
+    if sidecar_elem(ty).is_none() {
+        let msg = "`sidecar` requires a Vec, array or slice of a primitive numeric type";
+        return syn::Error::new_spanned(ty, msg).to_compile_error();
+    }
+    let field = field.to_lowercase();
+    let path = Ident::new("path", Span::call_site());
+    quote! {{
+        let mut bytes: Vec<u8> = Vec::new();
+        for v in (#value).iter() {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        // Named by a hash of the contents, so values of the same field don't collide.
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for b in &bytes {
+            hash ^= *b as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        let #path = format!("/rustifact_sidecar_{}_{:016x}.bin", #field, hash);
+        let out_dir = ::std::env::var("OUT_DIR").expect("rustifact: OUT_DIR is not set");
+        ::std::fs::write(format!("{}{}", out_dir, #path), &bytes)
+            .expect("rustifact: failed to write the sidecar file");
+        // Relative to `OUT_DIR` as seen by the crate including the artifact, so that the artifact
+        // doesn't depend on where it was built.
+        let #path = rustifact::ToTokenStream::to_tok_stream(&#path.as_str());
+        rustifact::internal::quote! { include_bytes!(concat!(env!("OUT_DIR"), ##path)) }
+    }}
+}
+
+/// A collection marked `chunk = N`, as a `&'static [&'static [T]]` of statics of at most `N`
+/// elements each, keeping each expression in the artifact tractable for rustc.
+fn chunk_toks(
+    ty: &Type,
+    value: &TokenStream,
+    size: usize,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> TokenStream {
+    let Some(elem) = seq_elem(ty) else {
+        return syn::Error::new_spanned(ty, "`chunk` requires a Vec, array or slice field")
+            .to_compile_error();
+    };
+    let static_elem = field_attrs.chunk_elem.as_ref().unwrap_or(elem);
+    let inner = elem_toks(elem, &quote! { v }, field_attrs, attrs);
+    let seq = seq_toks(&quote! { chunk.iter() }, &inner);
+    let (elems, len, chunks) = (
+        Ident::new("elems", Span::call_site()),
+        Ident::new("len", Span::call_site()),
+        Ident::new("chunks", Span::call_site()),
+    );
+    let helper = helper_attrs();
+    let docs = &field_attrs.docs;
+    let helper = quote! { #helper #(#[doc = #docs])* };
+    quote! {{
+        let mut #chunks = rustifact::internal::TokenStream::new();
+        for chunk in (#value).chunks(#size) {
+            let #elems = #seq;
+            let #len = rustifact::ToTokenStream::to_tok_stream(&chunk.len());
+            #chunks.extend(rustifact::internal::quote! {
+                { #helper static CHUNK: [#static_elem; ##len] = [##elems]; &CHUNK },
+            });
+        }
+        rustifact::internal::quote! { &[##chunks] }
+    }}
+}
+
+/// A collection marked `pad_to = N`, as an array `[T; N]` of its elements followed by copies of
+/// the value of `pad_with`, or of `T::default()` if none is given. Likewise for `array_len = N`,
+/// without padding, so that the collection must have exactly `N` elements.
+fn fixed_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field: &str,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    let (len, pad) = match (field_attrs.pad_to, field_attrs.array_len) {
+        (Some(_), Some(_)) => {
+            let msg = "`pad_to` and `array_len` can't be given together";
+            return Some(syn::Error::new_spanned(ty, msg).to_compile_error());
+        }
+        (Some(len), None) => (len, true),
+        (None, Some(len)) => (len, false),
+        (None, None) => return None,
+    };
+    let Some(elem) = seq_elem(ty) else {
+        let msg = if pad {
+            "`pad_to` requires a Vec, array or slice field"
+        } else {
+            "`array_len` requires a Vec, array or slice field"
+        };
+        return Some(syn::Error::new_spanned(ty, msg).to_compile_error());
+    };
+    let v = quote! { v };
+    let inner = match &field_attrs.with {
+        Some(with) => with.value_toks(&v),
+        None => elem_toks(elem, &v, field_attrs, attrs),
+    };
+    let elems = Ident::new("elems", Span::call_site());
+    if !pad {
+        let seq = seq_toks(&quote! { (#value).iter() }, &inner);
+        return Some(quote! {{
+            if (#value).len() != #len {
+                ::core::panic!(
+                    "rustifact: the field `{}` has {} elements, not array_len = {}",
+                    #field,
+                    (#value).len(),
+                    #len
+                );
+            }
+            let #elems = #seq;
+            rustifact::internal::quote! { [##elems] }
+        }});
+    }
+    let pad = match &field_attrs.pad_with {
+        Some(expr) => quote! { #expr },
+        None => quote! { <#elem as ::core::default::Default>::default() },
+    };
+    let seq = seq_toks(
+        &quote! {
+            (#value).iter().chain(::core::iter::repeat(&padding)).take(#len)
+        },
+        &inner,
+    );
+    Some(quote! {{
+        if (#value).len() > #len {
+            ::core::panic!(
+                "rustifact: the field `{}` has {} elements, more than pad_to = {}",
+                #field,
+                (#value).len(),
+                #len
+            );
+        }
+        let padding: #elem = #pad;
+        let #elems = #seq;
+        rustifact::internal::quote! { [##elems] }
+    }})
+}
+
+/// A field of bools marked `runs`, as a `&'static [(u32, u32)]` of the half-open intervals of its
+/// indices at which the value is `true`.
+fn runs_toks(value: &TokenStream) -> TokenStream {
+    let elems = Ident::new("elems", Span::call_site());
+    let seq = seq_toks(
+        &quote! { runs },
+        &quote! { rustifact::ToTokenStream::to_tok_stream(&v) },
+    );
+    quote! {{
+        let index = |i: usize| u32::try_from(i).expect("rustifact: too many flags for u32 indices");
+        let mut runs: Vec<(u32, u32)> = Vec::new();
+        let mut start = None;
+        let mut len = 0;
+        for (i, b) in (#value).iter().enumerate() {
+            match (*b, start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    runs.push((index(s), index(i)));
+                    start = None;
+                }
+                _ => {}
+            }
+            len = i + 1;
+        }
+        if let Some(s) = start {
+            runs.push((index(s), index(len)));
+        }
+        let #elems = #seq;
+        rustifact::internal::quote! { &[##elems] }
+    }}
+}
+
+/// A field marked `iter`, as a `&'static [T]` of the items of a clone of the field, which may then
+/// be any `IntoIterator + Clone`, such as a lazily evaluated iterator adapter.
+fn iter_toks(value: &TokenStream) -> TokenStream {
+    let elems = Ident::new("elems", Span::call_site());
+    let seq = seq_toks(
+        &quote! {
+            ::core::iter::IntoIterator::into_iter(::core::clone::Clone::clone(#value))
+        },
+        &quote! { rustifact::ToTokenStream::to_tok_stream(&v) },
+    );
+    quote! {{
+        let #elems = #seq;
+        rustifact::internal::quote! { &[##elems] }
+    }}
+}
+
+/// Any collection whose references implement `IntoIterator`, as a slice of its elements in
+/// iteration order, or sorted when the field is marked `sorted`, each passed through the field's
+/// converter if it has one.
+fn collection_toks(value: &TokenStream, field_attrs: &FieldAttrs) -> TokenStream {
+    let elems = Ident::new("elems", Span::call_site());
+    let v = quote! { v };
+    let inner = match &field_attrs.with {
+        Some(with) => with.value_toks(&v),
+        None => quote! { rustifact::ToTokenStream::to_tok_stream(#v) },
+    };
+    let items = if field_attrs.sorted {
+        quote! {
+            let mut items: Vec<_> = ::core::iter::IntoIterator::into_iter(#value).collect();
+            items.sort();
+        }
+    } else {
+        quote! { let items = ::core::iter::IntoIterator::into_iter(#value); }
+    };
+    let seq = seq_toks(&quote! { items }, &inner);
+    quote! {{
+        #items
+        let #elems = #seq;
+        rustifact::internal::quote! { &[##elems] }
+    }}
+}
+
+/// Tokens of a field marked `static_ref`, so that the out field is a `&'static` reference to the
+/// value. When its type is given, the value is hoisted into its own `static`, or a `const` if
+/// statics can't be referenced from consts before the `msrv`. `Vec` fields are emitted as arrays,
+/// the given type then being that of the elements.
+fn static_ref_toks(
+    ty: &Type,
+    value: &TokenStream,
+    static_ty: Option<(&Type, &Ident)>,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> TokenStream {
+    let v = Ident::new("v", Span::call_site());
+    let len = Ident::new("len", Span::call_site());
+    let (toks, is_vec) = referenced_toks(ty, value, field_attrs, attrs);
+    let helper = helper_attrs();
+    let docs = &field_attrs.docs;
+    let helper = quote! { #helper #(#[doc = #docs])* };
+    let kind = match attrs.supports(83) {
+        true => quote! { static },
+        false => quote! { const },
+    };
+    let element = match (static_ty, is_vec) {
+        (Some((static_ty, name)), true) => {
+            quote! { { #helper #kind #name: [#static_ty; ##len] = ##v; &#name } }
+        }
+        (Some((static_ty, name)), false) => {
+            quote! { { #helper #kind #name: #static_ty = ##v; &#name } }
+        }
+        (None, _) => quote! { &##v },
+    };
+    let len_toks = len_toks(value, is_vec);
+    quote! {{
+        let #v = #toks;
+        #len_toks
+        rustifact::internal::quote! { #element }
+    }}
+}
+
+/// Tokens of a field marked `hoist`, referencing the static of type `static_ty` into which the
+/// value is hoisted. The static is pushed to the `__rustifact_hoisted` items of the enclosing
+/// `to_toks_hoisted`, named after its `__rustifact_prefix` and `field`, and is a `const` if
+/// `__rustifact_const` is set.
+fn hoist_toks(
+    ty: &Type,
+    value: &TokenStream,
+    static_ty: &Type,
+    field: &str,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> TokenStream {
+    let (v, len, id, kind) = (
+        Ident::new("v", Span::call_site()),
+        Ident::new("len", Span::call_site()),
+        Ident::new("id", Span::call_site()),
+        Ident::new("kind", Span::call_site()),
+    );
+    let (toks, is_vec) = referenced_toks(ty, value, field_attrs, attrs);
+    let helper = helper_attrs();
+    let docs = &field_attrs.docs;
+    let static_ty = match is_vec {
+        true => quote! { [#static_ty; ##len] },
+        false => quote! { #static_ty },
+    };
+    let len_toks = len_toks(value, is_vec);
+    let field = field.to_uppercase();
+    quote! {{
+        let #v = #toks;
+        #len_toks
+        let #id = rustifact::internal::format_ident!("__{}_{}", __rustifact_prefix, #field);
+        let #kind = match __rustifact_const {
+            true => rustifact::internal::quote! { const },
+            false => rustifact::internal::quote! { static },
+        };
+        __rustifact_hoisted.extend(rustifact::internal::quote! {
+            #helper
+            #(#[doc = #docs])*
+            ##kind ##id: #static_ty = ##v;
+        });
+        rustifact::internal::quote! { &##id }
+    }}
+}
+
+/// Build-side expression evaluating to the tokens of a value of type `ty` to be referenced, and
+/// whether it's a `Vec`, emitted as an array.
+fn referenced_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> (TokenStream, bool) {
+    let v = Ident::new("v", Span::call_site());
+    let vec_elem = match (&field_attrs.with, type_args(ty, &["Vec"])) {
+        (None, Some(args)) => args.first().copied(),
+        _ => None,
+    };
+    match vec_elem {
+        Some(elem) => {
+            let inner = elem_toks(elem, &quote! { v }, field_attrs, attrs);
+            let seq = seq_toks(value, &inner);
+            let toks = quote! {{ let #v = #seq; rustifact::internal::quote! { [##v] } }};
+            (toks, true)
+        }
+        None => match &field_attrs.with {
+            Some(with) => (with.value_toks(value), false),
+            None => (elem_toks(ty, value, field_attrs, attrs), false),
+        },
+    }
+}
+
+/// The statement binding `len` to the tokens of the length of `value`, if it's a `Vec` emitted as
+/// an array.
+fn len_toks(value: &TokenStream, is_vec: bool) -> TokenStream {
+    let len = Ident::new("len", Span::call_site());
+    match is_vec {
+        true => quote! { let #len = rustifact::ToTokenStream::to_tok_stream(&(#value).len()); },
+        false => TokenStream::new(),
+    }
+}
+
+/// The out type of values of `ty` where it's known without an out type, for the statics of hoisted
+/// fields: primitives are emitted as themselves and strings as `&'static str`, within options,
+/// arrays and tuples.
+pub(crate) fn static_type(ty: &Type) -> Option<Type> {
+    let ty = ungroup(ty);
+    if let Type::Reference(r) = ty {
+        return path_named(&r.elem, &["str"]).map(|_| syn::parse_quote! { &'static str });
+    }
+    if let Some(args) = type_args(ty, &["Option"]) {
+        let inner = static_type(args.first()?)?;
+        return Some(syn::parse_quote! { Option<#inner> });
+    }
+    match ty {
+        Type::Array(a) => {
+            let (elem, len) = (static_type(&a.elem)?, &a.len);
+            Some(syn::parse_quote! { [#elem; #len] })
+        }
+        Type::Tuple(t) => {
+            let elems = t
+                .elems
+                .iter()
+                .map(static_type)
+                .collect::<Option<Vec<_>>>()?;
+            Some(syn::parse_quote! { (#(#elems,)*) })
+        }
+        _ if path_named(ty, NUMERIC).is_some() || path_named(ty, &["bool", "char"]).is_some() => {
+            Some(ty.clone())
+        }
+        _ if path_named(ty, &["String"]).is_some() => Some(syn::parse_quote! { &'static str }),
+        _ => None,
+    }
+}
+
+/// As `value_toks`, for values within a field, to which a converter doesn't apply.
+fn elem_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> TokenStream {
+    mapped_toks(ty, value, field_attrs, attrs)
+        .unwrap_or_else(|| quote! { (#value).to_tok_stream() })
+}
+
+/// Like `value_toks`, but `None` when `ty` has no special handling, even within its type arguments.
+fn mapped_toks(
+    ty: &Type,
+    value: &TokenStream,
+    field_attrs: &FieldAttrs,
+    attrs: &ContainerAttrs,
+) -> Option<TokenStream> {
+    if attrs.bindgen {
+        if let Some(toks) = bindgen_value_toks(ty, value) {
+            return Some(toks);
+        }
+    }
+    if let Some(toks) = nonzero_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = atomic_toks(ty, value) {
+        return Some(toks);
+    }
+    if let Some(toks) = interior_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = once_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = net_toks(ty, value) {
+        return Some(toks);
+    }
+    if let Some(toks) = uuid_toks(ty, value) {
+        return Some(toks);
+    }
+    if let Some(toks) = datetime_toks(ty, value) {
+        return Some(toks);
+    }
+    if let Some(toks) = bitflags_toks(ty, value, field_attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = float_bits_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = usize_as_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = radix_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = unsuffixed_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = owned_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = str_pointer_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = cow_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = weak_toks(ty) {
+        return Some(toks);
+    }
+    if let Some(toks) = string_like_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(args) = type_args(ty, &["Range", "RangeInclusive"]) {
+        let elem = args.first()?;
+        let (a, b) = (
+            Ident::new("a", Span::call_site()),
+            Ident::new("b", Span::call_site()),
+        );
+        // Ranges of ranges need their bounds parenthesised.
+        let (pa, pb) = if path_named(elem, &["Range", "RangeInclusive"]).is_some() {
+            (quote! { (##a) }, quote! { (##b) })
+        } else {
+            (quote! { ##a }, quote! { ##b })
+        };
+        let (start, end, range) = if path_named(ty, &["Range"]).is_some() {
+            let start = elem_toks(elem, &quote! { &r.start }, field_attrs, attrs);
+            let end = elem_toks(elem, &quote! { &r.end }, field_attrs, attrs);
+            (start, end, quote! { #pa..#pb })
+        } else {
+            let start = elem_toks(elem, &quote! { r.start() }, field_attrs, attrs);
+            let end = elem_toks(elem, &quote! { r.end() }, field_attrs, attrs);
+            (start, end, quote! { #pa..=#pb })
+        };
+        return Some(quote! {{
+            let r = #value;
+            let #a = #start;
+            let #b = #end;
+            rustifact::internal::quote! { #range }
+        }});
+    }
+    if let Some(toks) = state_machine_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = trie_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = multimap_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = map_slice_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = set_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = list_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = index_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Some(toks) = inline_vec_toks(ty, value, field_attrs, attrs) {
+        return Some(toks);
+    }
+    if let Type::Array(syn::TypeArray { elem, .. }) | Type::Slice(syn::TypeSlice { elem, .. }) =
+        ungroup(ty)
+    {
+        let inner = mapped_toks(elem, &quote! { v }, field_attrs, attrs)?;
+        let elems = Ident::new("elems", Span::call_site());
+        let seq = seq_toks(&quote! { (#value).iter() }, &inner);
+        return Some(quote! {{
+            let #elems = #seq;
+            rustifact::internal::quote! { [##elems] }
+        }});
+    }
+    if let Type::BareFn(_) = ungroup(ty) {
+        return Some(fn_toks(ty, value, field_attrs));
+    }
+    if let Type::Tuple(tuple) = ungroup(ty) {
+        return tuple_toks(tuple, value, field_attrs, attrs);
+    }
+    if let Some(args) = type_args(ty, &["Box"]) {
+        if let Type::TraitObject(_) = ungroup(args.first()?) {
+            return Some(dyn_toks(ty, value, field_attrs));
+        }
+        let inner = elem_toks(args.first()?, &quote! { &**(#value) }, field_attrs, attrs);
+        let b = Ident::new("b", Span::call_site());
+        return Some(quote! {{
+            let #b = #inner;
+            rustifact::internal::quote! { &##b }
+        }});
+    }
+    if let Some(args) = type_args(ty, &["Vec"]) {
+        let inner = mapped_toks(args.first()?, &quote! { v }, field_attrs, attrs)?;
+        let elems = Ident::new("elems", Span::call_site());
+        let seq = seq_toks(value, &inner);
+        return Some(quote! {{
+            let #elems = #seq;
+            rustifact::internal::quote! { vec![##elems] }
+        }});
+    }
+    if let Some(args) = type_args(ty, &["Result"]) {
+        let [ok_ty, err_ty] = args.as_slice() else {
+            return None;
+        };
+        let v = Ident::new("v", Span::call_site());
+        let ok = elem_toks(ok_ty, &quote! { #v }, field_attrs, attrs);
+        let err = elem_toks(err_ty, &quote! { #v }, field_attrs, attrs);
+        return Some(quote! {
+            match #value {
+                Ok(#v) => {
+                    let #v = #ok;
+                    rustifact::internal::quote! { Ok(##v) }
+                }
+                Err(#v) => {
+                    let #v = #err;
+                    rustifact::internal::quote! { Err(##v) }
+                }
+            }
+        });
+    }
+    if let Some(args) = type_args(ty, &["Option"]) {
+        let v = Ident::new("v", Span::call_site());
+        let inner = mapped_toks(args.first()?, &quote! { #v }, field_attrs, attrs)?;
+        return Some(quote! {
+            match #value {
+                Some(#v) => {
+                    let #v = #inner;
+                    rustifact::internal::quote! { Some(##v) }
+                }
+                None => rustifact::internal::quote! { None },
+            }
+        });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn static_type_of(ty: Type) -> Option<String> {
+        static_type(&ty).map(|t| quote! { #t }.to_string())
+    }
+
+    #[test]
+    fn static_types_map_strings_within_options_arrays_and_tuples() {
+        assert_eq!(static_type_of(parse_quote!(u16)).unwrap(), "u16");
+        assert_eq!(
+            static_type_of(parse_quote!(String)).unwrap(),
+            "& 'static str"
+        );
+        assert_eq!(
+            static_type_of(parse_quote!([Option<&'a str>; 2])).unwrap(),
+            "[Option < & 'static str > ; 2]"
+        );
+        assert_eq!(
+            static_type_of(parse_quote!((char, String))).unwrap(),
+            "(char , & 'static str ,)"
+        );
+        assert_eq!(static_type_of(parse_quote!(Vec<u8>)), None);
+        assert_eq!(static_type_of(parse_quote!(LevelIn)), None);
+    }
+
+    #[test]
+    fn numeric_out_fields_are_unsuffixed() {
+        for out_ty in [
+            parse_quote!(u32),
+            parse_quote!(&'static [f64]),
+            parse_quote!([Option<i8>; 4]),
+        ] {
+            let mut field_attrs = FieldAttrs::default();
+            select_for_out(&parse_quote!(Vec<u32>), &out_ty, &mut field_attrs);
+            assert!(field_attrs.unsuffixed, "{}", quote! { #out_ty });
+        }
+        let mut field_attrs = FieldAttrs::default();
+        select_for_out(
+            &parse_quote!(Vec<u32>),
+            &parse_quote!(Vec<Id>),
+            &mut field_attrs,
+        );
+        assert!(!field_attrs.unsuffixed);
+    }
+}
@@ -0,0 +1,174 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Emission of values as encoded byte blobs, decoded lazily at runtime.
+//!
+//! Rather than a literal constructor, the emitted expression has the form
+//! `::std::sync::LazyLock::new(|| decode(b"..."))`, so the out type of the
+//! artifact is `LazyLock<T>` where `T` is the usual out type, or once_cell's `Lazy<T>` for
+//! artifacts built before Rust 1.80.
+//!
+//! Besides the built-in encodings, a user-supplied encoder may be named with
+//! `#[rustifact(encoder = "path::to::Codec")]`. `Codec` must implement the `RustifactEncoder<T>`
+//! trait for the type `T` deriving, which `rustifact_encoder_trait!()` defines at the root of the
+//! crate using the derive, as a proc-macro crate can't export the trait itself. It provides
+//!
+//! * `fn encode(value: &T) -> Vec<u8>`, called at build time on the input value, and
+//! * `fn decoder(bytes: TokenStream) -> TokenStream`, returning the runtime expression decoding
+//!   the `&'static [u8]` expression `bytes` into the out type.
+//!
+//! This keeps the choice of serializer, and of the runtime crate doing the decoding, with the user.
+//!
+//! With `#[rustifact(compress = "zstd")]` the encoded bytes are compressed at build time, and
+//! decompressed within the lazy initializer before decoding, so `BYTES` is unchanged for decoders.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Ident, LitStr, Path};
+
+use crate::attr::ContainerAttrs;
+use crate::emit::{helper_attrs, lazy_path};
+
+pub(crate) enum Encoding {
+    Bincode,
+    Custom(Path),
+}
+
+impl Encoding {
+    pub fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        match lit.value().as_str() {
+            "bincode" => Ok(Encoding::Bincode),
+            other => Err(syn::Error::new(
+                lit.span(),
+                format!("unknown encoding '{}', expected \"bincode\"", other),
+            )),
+        }
+    }
+
+    pub fn from_encoder_lit(lit: &LitStr) -> syn::Result<Self> {
+        Ok(Encoding::Custom(lit.parse()?))
+    }
+
+    fn encode_expr(&self) -> TokenStream {
+        match self {
+            Encoding::Bincode => quote! {
+                ::bincode::serialize(self).expect("rustifact: bincode serialization failed")
+            },
+            Encoding::Custom(path) => quote! {
+                <#path as crate::RustifactEncoder<Self>>::encode(self)
+            },
+        }
+    }
+
+    /// Build-side code which adds the runtime decode expression to the element's tokens.
+    fn decode_toks(&self) -> TokenStream {
+        match self {
+            Encoding::Bincode => quote! {
+                rustifact::internal::quote! {
+                    ::bincode::deserialize(BYTES).expect("rustifact: bincode deserialization failed")
+                }
+            },
+            Encoding::Custom(path) => quote! {
+                <#path as crate::RustifactEncoder<Self>>::decoder(
+                    rustifact::internal::quote! { BYTES }
+                )
+            },
+        }
+    }
+}
+
+/// The definition of the `RustifactEncoder` trait, given by `rustifact_encoder_trait!()`.
+pub(crate) fn encoder_trait() -> TokenStream {
+    quote! {
+        /// An encoder of values of `T` as bytes at build time, and of the runtime expression
+        /// decoding them, for types marked `#[rustifact(encoder = "...")]`.
+        pub trait RustifactEncoder<T: ?::core::marker::Sized> {
+            /// The bytes encoding `value`.
+            fn encode(value: &T) -> ::std::vec::Vec<u8>;
+
+            /// The expression decoding the `&'static [u8]` expression `bytes` into the out type
+            /// of `T`.
+            fn decoder(bytes: rustifact::internal::TokenStream) -> rustifact::internal::TokenStream;
+        }
+    }
+}
+
+pub(crate) enum Compression {
+    Zstd,
+}
+
+impl Compression {
+    pub fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        match lit.value().as_str() {
+            "zstd" if cfg!(feature = "zstd") => Ok(Compression::Zstd),
+            "zstd" => Err(syn::Error::new(
+                lit.span(),
+                "compression with zstd requires the `zstd` feature of rustifact_derive",
+            )),
+            other => Err(syn::Error::new(
+                lit.span(),
+                format!("unknown compression '{}', expected \"zstd\"", other),
+            )),
+        }
+    }
+
+    fn compress_expr(&self) -> TokenStream {
+        match self {
+            Compression::Zstd => quote! {
+                ::zstd::encode_all(&bytes[..], 19).expect("rustifact: zstd compression failed")
+            },
+        }
+    }
+
+    /// The runtime expression decompressing the `COMPRESSED` static into a `Vec<u8>`.
+    fn decompress_expr(&self) -> TokenStream {
+        match self {
+            Compression::Zstd => quote! {
+                ::zstd::decode_all(COMPRESSED).expect("rustifact: zstd decompression failed")
+            },
+        }
+    }
+}
+
+pub(crate) fn get_encoded_body(encoding: &Encoding, attrs: &ContainerAttrs) -> TokenStream {
+    let lazy = lazy_path(attrs);
+    let lit = Ident::new("lit", Span::call_site());
+    let mut encode = encoding.encode_expr();
+    let mut bytes_item = quote! { static BYTES: &[u8] = ##lit; };
+    if let Some(compression) = &attrs.compress {
+        let compress = compression.compress_expr();
+        let decompress = compression.decompress_expr();
+        encode = quote! {{
+            let bytes: Vec<u8> = #encode;
+            #compress
+        }};
+        bytes_item = quote! {
+            static COMPRESSED: &[u8] = ##lit;
+            #[allow(non_snake_case)]
+            let BYTES: &'static [u8] = ::std::vec::Vec::leak(#decompress);
+        };
+    }
+    let decode = encoding.decode_toks();
+    let decoder = Ident::new("decoder", Span::call_site());
+    let helper = helper_attrs();
+    quote! {
+        let bytes: Vec<u8> = #encode;
+        let mut #lit = String::with_capacity(bytes.len() * 4 + 3);
+        #lit.push_str("b\"");
+        for b in &bytes {
+            #lit.push_str(&format!("\\x{:02x}", b));
+        }
+        #lit.push('"');
+        let #lit: rustifact::internal::TokenStream = #lit.parse().unwrap();
+        let #decoder: rustifact::internal::TokenStream = #decode;
+        let element = rustifact::internal::quote! {
+            #lazy::new(|| {
+                #helper
+                #bytes_item
+                ##decoder
+            })
+        };
+        toks.extend(element);
+    }
+}
@@ -0,0 +1,115 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! A stable hash of a type definition, for detecting schema drift.
+
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::DeriveInput;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+fn fnv1a(hash: &mut u64, bytes: &[u8]) {
+    for b in bytes {
+        *hash ^= *b as u64;
+        *hash = hash.wrapping_mul(FNV_PRIME);
+    }
+}
+
+/// Hashes each token separately, so the result doesn't depend on how tokens are spaced when
+/// printed, nor on trailing commas, as added by rustfmt when fields go on separate lines.
+fn hash_tokens(hash: &mut u64, toks: TokenStream) {
+    let mut toks: Vec<TokenTree> = toks.into_iter().collect();
+    if matches!(toks.last(), Some(TokenTree::Punct(p)) if p.as_char() == ',') {
+        toks.pop();
+    }
+    for tt in toks {
+        match tt {
+            TokenTree::Group(g) => {
+                let (open, close) = match g.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => ("", ""),
+                };
+                fnv1a(hash, open.as_bytes());
+                hash_tokens(hash, g.stream());
+                fnv1a(hash, close.as_bytes());
+            }
+            tt => {
+                fnv1a(hash, tt.to_string().as_bytes());
+                fnv1a(hash, b" ");
+            }
+        }
+    }
+}
+
+/// The fingerprint of the definition of `ast`, covering its name, generics, fields, field types
+/// and attributes, but not its doc comments.
+pub(crate) fn fingerprint(ast: &DeriveInput) -> u64 {
+    let mut ast = ast.clone();
+    let is_doc = |a: &syn::Attribute| a.path().is_ident("doc");
+    ast.attrs.retain(|a| !is_doc(a));
+    let fields = match &mut ast.data {
+        syn::Data::Struct(data) => data.fields.iter_mut().collect::<Vec<_>>(),
+        syn::Data::Enum(data) => data
+            .variants
+            .iter_mut()
+            .flat_map(|v| {
+                v.attrs.retain(|a| !is_doc(a));
+                v.fields.iter_mut()
+            })
+            .collect(),
+        syn::Data::Union(data) => data.fields.named.iter_mut().collect(),
+    };
+    for f in fields {
+        f.attrs.retain(|a| !is_doc(a));
+    }
+    let mut hash = FNV_OFFSET;
+    hash_tokens(&mut hash, ast.into_token_stream());
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn fingerprint_ignores_doc_comments() {
+        let a: DeriveInput = parse_quote! {
+            struct Config { level: u8 }
+        };
+        let b: DeriveInput = parse_quote! {
+            /// The configuration.
+            struct Config {
+                /// The level.
+                level: u8,
+            }
+        };
+        assert_eq!(fingerprint(&a), fingerprint(&b));
+    }
+
+    #[test]
+    fn fingerprint_covers_fields_and_attributes() {
+        let base: DeriveInput = parse_quote! { struct Config { level: u8 } };
+        let retyped: DeriveInput = parse_quote! { struct Config { level: u16 } };
+        let renamed: DeriveInput = parse_quote! { struct Config { rank: u8 } };
+        let marked: DeriveInput = parse_quote! {
+            struct Config { #[rustifact(float_bits)] level: u8 }
+        };
+        let fp = fingerprint(&base);
+        assert_ne!(fp, fingerprint(&retyped));
+        assert_ne!(fp, fingerprint(&renamed));
+        assert_ne!(fp, fingerprint(&marked));
+    }
+
+    #[test]
+    fn fingerprint_distinguishes_grouping() {
+        let a: DeriveInput = parse_quote! { struct S { a: (u8, (u8, u8)) } };
+        let b: DeriveInput = parse_quote! { struct S { a: ((u8, u8), u8) } };
+        assert_ne!(fingerprint(&a), fingerprint(&b));
+    }
+}
@@ -6,47 +6,227 @@
 //!
 //! This crate serves to provide a derive macro for the `rustifact::ToTokenStream` trait. You should not need
 //! to use this crate directly, as it's exposed via the `rustifact` crate.
+//!
+#![doc = include_str!("reference.md")]
+
+mod arena;
+mod attr;
+mod bound;
+mod case;
+mod convert;
+mod emit;
+mod encode;
+mod fingerprint;
 
-use proc_macro2::TokenStream;
-use quote::quote;
+use attr::{ContainerAttrs, FieldAttrs, VariantAttrs};
+use emit::value_toks;
+use encode::{encoder_trait, get_encoded_body};
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
 use syn::{
-    parse_macro_input, Data, DataEnum, DataStruct, DeriveInput, Fields, FieldsNamed, FieldsUnnamed,
-    Ident, Index,
+    parse_macro_input, Data, DataEnum, DataStruct, DataUnion, DeriveInput, Field, Fields,
+    FieldsNamed, FieldsUnnamed, GenericArgument, GenericParam, Ident, ImplItem, Index, Item,
+    PathArguments, Stmt, Type,
 };
 
-fn get_struct_body(out_type: &Ident, data: &DataStruct) -> TokenStream {
-    match &data.fields {
+fn no_cfg(fields: &Fields) -> syn::Result<()> {
+    for f in fields.iter() {
+        if FieldAttrs::from_attrs(&f.attrs)?.cfg.is_some() {
+            return Err(syn::Error::new_spanned(
+                f,
+                "`cfg` is only supported on named struct fields",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// `toks` with each `Self` replaced by `with`, other than in paths such as `Self::Item`.
+fn replace_self(toks: TokenStream, with: &TokenStream) -> TokenStream {
+    let mut out = TokenStream::new();
+    let mut iter = toks.into_iter().peekable();
+    while let Some(tt) = iter.next() {
+        match tt {
+            TokenTree::Ident(id) if id == "Self" => {
+                let is_path =
+                    matches!(iter.peek(), Some(TokenTree::Punct(p)) if p.as_char() == ':');
+                if is_path {
+                    out.extend([TokenTree::Ident(id)]);
+                } else {
+                    out.extend(with.clone());
+                }
+            }
+            TokenTree::Group(g) => {
+                let mut group = Group::new(g.delimiter(), replace_self(g.stream(), with));
+                group.set_span(g.span());
+                out.extend([TokenTree::Group(group)]);
+            }
+            tt => out.extend([tt]),
+        }
+    }
+    out
+}
+
+/// Replaces `Self` in the field types of `ast` by the type itself, so recursive types written with
+/// `Self` are mapped and fingerprinted like those naming the type.
+fn normalize_self(ast: &mut DeriveInput) {
+    let ident = &ast.ident;
+    let (_, ty_generics, _) = ast.generics.split_for_impl();
+    let with = quote! { #ident #ty_generics };
+    let fields: Vec<&mut Field> = match &mut ast.data {
+        Data::Struct(data) => data.fields.iter_mut().collect(),
+        Data::Enum(data) => data
+            .variants
+            .iter_mut()
+            .flat_map(|v| v.fields.iter_mut())
+            .collect(),
+        Data::Union(data) => data.fields.named.iter_mut().collect(),
+    };
+    for f in fields {
+        if let Ok(ty) = syn::parse2(replace_self(f.ty.to_token_stream(), &with)) {
+            f.ty = ty;
+        }
+    }
+}
+
+/// The name of a named field in the out type.
+fn out_field_ident(ident: &Ident, attrs: &ContainerAttrs) -> Ident {
+    let name = ident.to_string();
+    match attrs
+        .strip_prefix
+        .as_deref()
+        .and_then(|p| name.strip_prefix(p))
+    {
+        Some(stripped) if !stripped.is_empty() => Ident::new(stripped, ident.span()),
+        _ => ident.clone(),
+    }
+}
+
+/// The named field `ident` of a struct, for container attributes referring to fields.
+fn named_field<'a>(data: &'a Data, ident: &Ident) -> syn::Result<&'a Field> {
+    let Data::Struct(DataStruct {
+        fields: Fields::Named(named),
+        ..
+    }) = data
+    else {
+        return Err(syn::Error::new(
+            ident.span(),
+            "only structs with named fields can refer to fields by name",
+        ));
+    };
+    named
+        .named
+        .iter()
+        .find(|f| f.ident.as_ref() == Some(ident))
+        .ok_or_else(|| syn::Error::new(ident.span(), format!("no field named '{}'", ident)))
+}
+
+fn get_struct_body(
+    out_type: &Ident,
+    data: &DataStruct,
+    attrs: &ContainerAttrs,
+) -> syn::Result<TokenStream> {
+    let out_path = out_path_toks(out_type, attrs);
+    let body = match &data.fields {
         Fields::Named(FieldsNamed { named, .. }) => {
             let mut init_toks = TokenStream::new();
             let mut fields = TokenStream::new();
-            for f in named.iter() {
-                let ident = &f.ident;
-                init_toks.extend(quote! { let #ident = self.#ident.to_tok_stream(); });
-                fields.extend(quote! { #ident: ##ident, });
+            // With `base`, the fields differing from it, as collected when `to_toks` runs.
+            let base_fields = Ident::new("__rustifact_fields", Span::call_site());
+            if let Some(packed) = &attrs.bitpack {
+                let (bools, bits_ty) = bitpack_fields(named.iter().collect())?;
+                let bits = Ident::new("rustifact_bits", Span::call_site());
+                let shifts = 0..bools.len();
+                init_toks.extend(quote! {
+                    let mut #bits: #bits_ty = 0;
+                    #(if self.#bools { #bits |= 1 << #shifts; })*
+                    let #bits = rustifact::ToTokenStream::to_tok_stream(&#bits);
+                });
+                fields.extend(quote! { #packed: ##bits, });
             }
-            quote! {
-                #init_toks
-                let element = rustifact::internal::quote! {
-                    #out_type {
-                        #fields
+            let mut set: Vec<Ident> = attrs.bitpack.iter().cloned().collect();
+            for (i, f) in named.iter().enumerate() {
+                if attrs.bitpack.is_some() && emit::is_bool(&f.ty) {
+                    continue;
+                }
+                let mut field_attrs = FieldAttrs::from_attrs(&f.attrs)?;
+                let ident = f.ident.as_ref().unwrap();
+                let out_ident = out_field_ident(ident, attrs);
+                if let Some(out_fields) = &attrs.out_fields {
+                    let Some((_, out_ty)) = out_fields.iter().find(|(i, _)| *i == out_ident) else {
+                        let msg = format!("no field `{}` in the fields of the out type", out_ident);
+                        return Err(syn::Error::new_spanned(ident, msg));
+                    };
+                    emit::select_for_out(&f.ty, out_ty, &mut field_attrs);
+                }
+                set.push(out_ident.clone());
+                let field = ident.to_string();
+                let value = value_toks(&f.ty, quote! { &self.#ident }, &field, &field_attrs, attrs);
+                let cfg = field_attrs.cfg.as_ref().map(|cfg| quote! { #[cfg(#cfg)] });
+                // Locals are numbered, so as not to shadow those of the body.
+                let local = Ident::new(&format!("ident{}", i), Span::call_site());
+                match &attrs.base {
+                    // Fields equal to those of the base are left to the struct update syntax.
+                    Some(base) => init_toks.extend(quote! {
+                        if self.#ident != #base.#ident {
+                            let #local = #value;
+                            #base_fields.extend(rustifact::internal::quote! { #cfg #out_ident: ##local, });
+                        }
+                    }),
+                    None => {
+                        init_toks.extend(quote! { let #local = #value; });
+                        fields.extend(quote! { #cfg #out_ident: ##local, });
                     }
-                };
-                toks.extend(element);
+                }
+            }
+            if let (Some(out_fields), None) = (&attrs.out_fields, &attrs.base) {
+                if let Some((missing, _)) = out_fields.iter().find(|(i, _)| !set.contains(i)) {
+                    let msg = format!("the out field `{}` isn't set by any field", missing);
+                    return Err(syn::Error::new_spanned(missing, msg));
+                }
+            }
+            match &attrs.base {
+                // Those of `fields`, such as the packed bools, are always emitted.
+                Some(base) => quote! {
+                    let mut #base_fields = rustifact::internal::TokenStream::new();
+                    #init_toks
+                    let element = rustifact::internal::quote! {
+                        #out_path {
+                            #fields
+                            ##base_fields
+                            ..#base
+                        }
+                    };
+                    toks.extend(element);
+                },
+                None => quote! {
+                    #init_toks
+                    let element = rustifact::internal::quote! {
+                        #out_path {
+                            #fields
+                        }
+                    };
+                    toks.extend(element);
+                },
             }
         }
         Fields::Unnamed(FieldsUnnamed { unnamed, .. }) => {
+            no_cfg(&data.fields)?;
             let mut init_toks = TokenStream::new();
             let mut fields = TokenStream::new();
-            for i in 0..unnamed.len() {
+            for (i, f) in unnamed.iter().enumerate() {
                 let index = Index::from(i);
-                let ident = Ident::new(&format!("ident{}", i), out_type.span());
-                init_toks.extend(quote! { let #ident = self.#index.to_tok_stream(); });
+                let ident = Ident::new(&format!("ident{}", i), Span::call_site());
+                let field_attrs = FieldAttrs::from_attrs(&f.attrs)?;
+                let field = i.to_string();
+                let value = value_toks(&f.ty, quote! { &self.#index }, &field, &field_attrs, attrs);
+                init_toks.extend(quote! { let #ident = #value; });
                 fields.extend(quote! { ##ident, });
             }
             quote! {
                 #init_toks
                 let element = rustifact::internal::quote! {
-                    #out_type ( #fields )
+                    #out_path ( #fields )
                 };
                 toks.extend(element);
             }
@@ -54,52 +234,563 @@ fn get_struct_body(out_type: &Ident, data: &DataStruct) -> TokenStream {
         Fields::Unit => {
             quote! { () }
         }
+    };
+    Ok(body)
+}
+
+fn get_union_body(
+    out_type: &Ident,
+    data: &DataUnion,
+    attrs: &ContainerAttrs,
+) -> syn::Result<TokenStream> {
+    let Some(ident) = &attrs.union_field else {
+        return Err(syn::Error::new(
+            out_type.span(),
+            "unions require `#[rustifact(union_field = \"...\")]` naming the field to emit",
+        ));
+    };
+    let Some(f) = data
+        .fields
+        .named
+        .iter()
+        .find(|f| f.ident.as_ref() == Some(ident))
+    else {
+        return Err(syn::Error::new(ident.span(), "no such union field"));
+    };
+    let out_ident = out_field_ident(ident, attrs);
+    let out_path = out_path_toks(out_type, attrs);
+    let field_attrs = FieldAttrs::from_attrs(&f.attrs)?;
+    let value = value_toks(
+        &f.ty,
+        quote! { unsafe { &self.#ident } },
+        &ident.to_string(),
+        &field_attrs,
+        attrs,
+    );
+    Ok(quote! {
+        let #ident = #value;
+        let element = rustifact::internal::quote! {
+            #out_path { #out_ident: ##ident }
+        };
+        toks.extend(element);
+    })
+}
+
+/// The tokens naming the out type in emitted values, which with a path prefix refer to the
+/// `out_path` variable bound by `out_path_binding`.
+fn out_path_toks(out_type: &Ident, attrs: &ContainerAttrs) -> TokenStream {
+    if attrs.path_prefix.is_some() || attrs.path_prefix_env.is_some() {
+        let out_path = Ident::new("out_path", Span::call_site());
+        quote! { ##out_path }
+    } else {
+        let args = out_args(attrs);
+        quote! { #out_type #args }
+    }
+}
+
+/// The generic arguments of the out type, in the `::<...>` form valid in expressions.
+fn out_args(attrs: &ContainerAttrs) -> TokenStream {
+    match &attrs.out_args {
+        PathArguments::AngleBracketed(args) => {
+            let args = &args.args;
+            quote! { ::<#args> }
+        }
+        _ => TokenStream::new(),
+    }
+}
+
+/// Build-side statement binding `out_path` to the path of the out type, with its prefix from the
+/// `path_prefix_env` variable if set, or else from `path_prefix`.
+fn out_path_binding(out_type: &Ident, attrs: &ContainerAttrs) -> TokenStream {
+    if attrs.path_prefix.is_none() && attrs.path_prefix_env.is_none() {
+        return TokenStream::new();
+    }
+    let default = attrs.path_prefix.clone().unwrap_or_default();
+    let prefix = match &attrs.path_prefix_env {
+        Some(env) => quote! { ::std::env::var(#env).unwrap_or_else(|_| #default.to_string()) },
+        None => quote! { #default.to_string() },
+    };
+    let name = out_type.to_string() + &out_args(attrs).to_string();
+    quote! {
+        let out_path: rustifact::internal::TokenStream = {
+            let prefix = #prefix;
+            let path = if prefix.is_empty() {
+                #name.to_string()
+            } else {
+                format!("{}::{}", prefix.trim_end_matches("::"), #name)
+            };
+            path.parse().expect("rustifact: invalid out type path prefix")
+        };
+    }
+}
+
+/// The match arm for variants unknown to the derive, if the enum is non-exhaustive or a fallback is
+/// given, which emits the fallback or panics.
+fn fallback_arm(
+    in_type: &Ident,
+    attrs: &ContainerAttrs,
+    other: Option<&TokenStream>,
+) -> TokenStream {
+    let emit = match (&attrs.fallback, other) {
+        (Some(fallback), _) => quote! { rustifact::internal::quote! { #fallback } },
+        (None, Some(other)) if attrs.non_exhaustive => other.clone(),
+        (None, _) if attrs.non_exhaustive => {
+            let msg = format!(
+                "rustifact: no emission for an unknown variant of {}, which may be given with #[rustifact(fallback = \"...\")]",
+                in_type
+            );
+            quote! { ::core::panic!(#msg) }
+        }
+        (None, _) => return TokenStream::new(),
+    };
+    quote! {
+        #[allow(unreachable_patterns)]
+        _ => { #emit }
+    }
+}
+
+fn get_as_str_body(
+    in_type: &Ident,
+    data: &Data,
+    attrs: &ContainerAttrs,
+) -> syn::Result<TokenStream> {
+    let Data::Enum(data) = data else {
+        return Err(syn::Error::new(
+            in_type.span(),
+            "`as_str` is only supported on enums",
+        ));
+    };
+    let mut arms = TokenStream::new();
+    for v in &data.variants {
+        let ident = &v.ident;
+        let name = match VariantAttrs::from_attrs(&v.attrs)?.rename {
+            Some(rename) => rename,
+            None => match attrs.rename_all_variants {
+                Some(case) => case.apply(&ident.to_string()),
+                None => ident.to_string(),
+            },
+        };
+        arms.extend(quote! {
+            Self::#ident { .. } => rustifact::ToTokenStream::to_tok_stream(&#name),
+        });
     }
+    arms.extend(fallback_arm(in_type, attrs, None));
+    Ok(quote! {
+        let element = match self {
+            #arms
+        };
+        toks.extend(element);
+    })
+}
+
+/// The name of a variant in the out type, given by its `rename`, or else converted by
+/// `rename_all_variants`.
+fn out_variant_ident(
+    ident: &Ident,
+    variant_attrs: &VariantAttrs,
+    attrs: &ContainerAttrs,
+) -> syn::Result<Ident> {
+    let name = match (&variant_attrs.rename, attrs.rename_all_variants) {
+        (Some(rename), _) => rename.clone(),
+        (None, Some(case)) => case.apply(&ident.to_string()),
+        (None, None) => return Ok(ident.clone()),
+    };
+    syn::parse_str::<Ident>(&name)
+        .map(|id| Ident::new(&id.to_string(), ident.span()))
+        .map_err(|_| {
+            syn::Error::new(
+                ident.span(),
+                format!("renamed variant '{}' is not a valid identifier", name),
+            )
+        })
 }
 
-fn get_enum_body(out_type: &Ident, data: &DataEnum) -> TokenStream {
+fn get_enum_body(
+    in_type: &Ident,
+    out_type: &Ident,
+    data: &DataEnum,
+    attrs: &ContainerAttrs,
+) -> syn::Result<TokenStream> {
+    let out_path = out_path_toks(out_type, attrs);
+    let mut other = None;
+    for v in &data.variants {
+        let variant_attrs = VariantAttrs::from_attrs(&v.attrs)?;
+        if !variant_attrs.other {
+            continue;
+        }
+        if other.is_some() {
+            let msg = "only one variant may be marked `other`";
+            return Err(syn::Error::new_spanned(&v.ident, msg));
+        }
+        if !matches!(v.fields, Fields::Unit) {
+            let msg = "the `other` variant must be a unit variant";
+            return Err(syn::Error::new_spanned(&v.ident, msg));
+        }
+        let out_ident = out_variant_ident(&v.ident, &variant_attrs, attrs)?;
+        other = Some(quote! { rustifact::internal::quote! { #out_path::#out_ident } });
+    }
     let mut arms = TokenStream::new();
     for v in &data.variants {
+        no_cfg(&v.fields)?;
+        let variant_attrs = VariantAttrs::from_attrs(&v.attrs)?;
         let ident = &v.ident;
+        let out_ident = out_variant_ident(ident, &variant_attrs, attrs)?;
+        if variant_attrs.as_other {
+            let Some(other) = &other else {
+                let msg = "`as_other` requires a variant marked `other`";
+                return Err(syn::Error::new_spanned(ident, msg));
+            };
+            arms.extend(quote! { Self::#ident { .. } => #other, });
+            continue;
+        }
         let toks = match &v.fields {
             Fields::Unnamed(fields_unnamed) => {
                 let mut init_toks = TokenStream::new();
                 let mut fields = TokenStream::new();
                 let mut fields_out = TokenStream::new();
-                for i in 0..fields_unnamed.unnamed.len() {
-                    let id = Ident::new(&format!("ident{}", i), out_type.span());
-                    let id_toks = Ident::new(&format!("ident{}_toks", i), out_type.span());
-                    init_toks.extend(quote! { let #id_toks = #id.to_tok_stream(); });
+                for (i, f) in fields_unnamed.unnamed.iter().enumerate() {
+                    let id = Ident::new(&format!("ident{}", i), Span::call_site());
+                    let id_toks = Ident::new(&format!("ident{}_toks", i), Span::call_site());
+                    let field_attrs = FieldAttrs::from_attrs(&f.attrs)?;
+                    let field = format!("{}_{}", ident, i);
+                    let value = value_toks(&f.ty, quote! { #id }, &field, &field_attrs, attrs);
+                    init_toks.extend(quote! { let #id_toks = #value; });
                     fields.extend(quote! { #id, });
                     fields_out.extend(quote! { ##id_toks, });
                 }
                 if fields.is_empty() {
                     quote! {
-                        #out_type::#ident => rustifact::internal::quote! { #out_type::#ident },
+                        Self::#ident => rustifact::internal::quote! { #out_path::#out_ident },
                     }
                 } else {
                     quote! {
-                        #out_type::#ident( #fields ) => {
+                        Self::#ident( #fields ) => {
                             #init_toks
-                            rustifact::internal::quote! { #out_type::#ident( #fields_out ) }
+                            rustifact::internal::quote! { #out_path::#out_ident( #fields_out ) }
                         },
                     }
                 }
             }
-            Fields::Named(_) => {
-                panic!("Named fields are not yet supported");
+            Fields::Named(fields_named) => {
+                let mut init_toks = TokenStream::new();
+                let mut fields = TokenStream::new();
+                let mut fields_out = TokenStream::new();
+                for (i, f) in fields_named.named.iter().enumerate() {
+                    let field_ident = f.ident.as_ref().unwrap();
+                    let out_field = out_field_ident(field_ident, attrs);
+                    // Bound under other names, so fields can't shadow the locals of the body.
+                    let id = Ident::new(&format!("ident{}", i), Span::call_site());
+                    let id_toks = Ident::new(&format!("ident{}_toks", i), Span::call_site());
+                    let field_attrs = FieldAttrs::from_attrs(&f.attrs)?;
+                    let field = format!("{}_{}", ident, field_ident);
+                    let value = value_toks(&f.ty, quote! { #id }, &field, &field_attrs, attrs);
+                    init_toks.extend(quote! { let #id_toks = #value; });
+                    fields.extend(quote! { #field_ident: #id, });
+                    fields_out.extend(quote! { #out_field: ##id_toks, });
+                }
+                quote! {
+                    Self::#ident { #fields } => {
+                        #init_toks
+                        rustifact::internal::quote! { #out_path::#out_ident { #fields_out } }
+                    },
+                }
             }
             Fields::Unit => {
-                quote! { #out_type::#ident => rustifact::internal::quote! { #out_type::#ident }, }
+                quote! { Self::#ident => rustifact::internal::quote! { #out_path::#out_ident }, }
             }
         };
         arms.extend(toks);
     }
-    quote! {
+    if arms.is_empty() {
+        // No value of an enum without variants exists to be emitted.
+        return Ok(quote! { match *self {} });
+    }
+    arms.extend(fallback_arm(in_type, attrs, other.as_ref()));
+    Ok(quote! {
         let element = match self {
             #arms
         };
         toks.extend(element);
+    })
+}
+
+/// Whether the attributes of any field of `data` satisfy `pred`.
+fn has_field(data: &Data, pred: impl Fn(&FieldAttrs) -> bool) -> bool {
+    all_fields(data)
+        .into_iter()
+        .any(|f| FieldAttrs::from_attrs(&f.attrs).is_ok_and(|a| pred(&a)))
+}
+
+/// The `bool` fields of a struct marked `bitpack`, each packed into a bit of an integer of the
+/// returned type, in order from the least significant.
+fn bitpack_fields(fields: Vec<&Field>) -> syn::Result<(Vec<&Ident>, Ident)> {
+    let bools: Vec<&Ident> = fields
+        .into_iter()
+        .filter(|f| emit::is_bool(&f.ty))
+        .filter_map(|f| f.ident.as_ref())
+        .collect();
+    let bits = match bools.len() {
+        0..=8 => "u8",
+        9..=16 => "u16",
+        17..=32 => "u32",
+        33..=64 => "u64",
+        65..=128 => "u128",
+        n => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                format!("`bitpack` can pack at most 128 bools, but there are {}", n),
+            ))
+        }
+    };
+    Ok((bools, Ident::new(bits, Span::call_site())))
+}
+
+/// Build-side statements running the `validate` functions of the type and its fields, and checking
+/// the keys of fields marked `unique_key`, stopping the build with the error and the path of the
+/// value on failure.
+fn validate_toks(ast: &DeriveInput, attrs: &ContainerAttrs) -> syn::Result<TokenStream> {
+    let check = |path: &syn::Path, value: TokenStream, what: String| {
+        quote! {
+            if let ::core::result::Result::Err(e) = #path(#value) {
+                ::core::panic!("rustifact: validation of {} failed: {}", #what, e);
+            }
+        }
+    };
+    let in_type = &ast.ident;
+    let mut toks = TokenStream::new();
+    if let Some(path) = &attrs.validate {
+        toks.extend(check(path, quote! { self }, in_type.to_string()));
+    }
+    let Data::Struct(data) = &ast.data else {
+        if let Some(f) = all_fields(&ast.data).into_iter().find(|f| {
+            FieldAttrs::from_attrs(&f.attrs)
+                .is_ok_and(|a| a.validate.is_some() || a.unique_key.is_some())
+        }) {
+            let msg = "`validate` and `unique_key` are only supported on the fields of structs";
+            return Err(syn::Error::new_spanned(f, msg));
+        }
+        return Ok(toks);
+    };
+    for (i, f) in data.fields.iter().enumerate() {
+        let field_attrs = FieldAttrs::from_attrs(&f.attrs)?;
+        let (member, name) = match &f.ident {
+            Some(ident) => (quote! { #ident }, ident.to_string()),
+            None => {
+                let index = Index::from(i);
+                (quote! { #index }, i.to_string())
+            }
+        };
+        let what = format!("{}.{}", in_type, name);
+        if let Some(path) = &field_attrs.validate {
+            toks.extend(check(path, quote! { &self.#member }, what.clone()));
+        }
+        if let Some(key) = &field_attrs.unique_key {
+            toks.extend(quote! {{
+                let mut seen = ::std::collections::HashMap::new();
+                for item in ::core::iter::IntoIterator::into_iter(&self.#member) {
+                    if let ::core::option::Option::Some(prev) = seen.insert(&item.#key, item) {
+                        ::core::panic!(
+                            "rustifact: elements of {} share the key {:?}:\n{:?}\n{:?}",
+                            #what,
+                            item.#key,
+                            prev,
+                            item
+                        );
+                    }
+                }
+            }});
+        }
+    }
+    Ok(toks)
+}
+
+/// The `ToTokenStream` impl of the out type for `chain`, emitting each of the fields declared by
+/// `OutFields` as is, so an artifact can be emitted again by a later build stage.
+fn chain_impl(out_type: &Ident, attrs: &ContainerAttrs) -> syn::Result<TokenStream> {
+    let Some(out_fields) = &attrs.out_fields else {
+        let msg = "`chain` needs the fields of the out type, given by #[OutFields(...)]";
+        return Err(syn::Error::new_spanned(out_type, msg));
+    };
+    if attrs.out_type.is_none() {
+        let msg = "`chain` needs an out type other than the type itself, given by #[OutType(...)]";
+        return Err(syn::Error::new_spanned(out_type, msg));
+    }
+    let out_args = &attrs.out_args;
+    let out_path_binding = out_path_binding(out_type, attrs);
+    let out_path = out_path_toks(out_type, attrs);
+    let mut init = TokenStream::new();
+    let mut fields = TokenStream::new();
+    for (ident, ty) in out_fields {
+        init.extend(quote! { let #ident = rustifact::ToTokenStream::to_tok_stream(&self.#ident); });
+        // References emit their referent, which is borrowed again, except for string literals.
+        let borrow = match emit::ungroup(ty) {
+            Type::Reference(r) if emit::path_named(&r.elem, &["str"]).is_none() => quote! { & },
+            _ => TokenStream::new(),
+        };
+        fields.extend(quote! { #ident: #borrow ##ident, });
+    }
+    Ok(quote! {
+        impl rustifact::ToTokenStream for #out_type #out_args {
+            fn to_toks(&self, toks: &mut rustifact::internal::TokenStream) {
+                #out_path_binding
+                #init
+                toks.extend(rustifact::internal::quote! { #out_path { #fields } });
+            }
+        }
+    })
+}
+
+/// For each type of `impl_for`, the type and the statements aliasing the type and const
+/// parameters of `ast` to its generic arguments, as in `type T = u8;`.
+fn instantiations(ast: &DeriveInput, types: &[Type]) -> syn::Result<Vec<(Type, TokenStream)>> {
+    let params: Vec<&GenericParam> = ast
+        .generics
+        .params
+        .iter()
+        .filter(|p| !matches!(p, GenericParam::Lifetime(_)))
+        .collect();
+    let mut out = Vec::new();
+    for ty in types {
+        let segment = match ty {
+            Type::Path(tp) if tp.qself.is_none() => tp.path.segments.last(),
+            _ => None,
+        };
+        let args: Vec<&GenericArgument> = match segment {
+            Some(segment) if segment.ident == ast.ident => match &segment.arguments {
+                PathArguments::AngleBracketed(args) => args
+                    .args
+                    .iter()
+                    .filter(|a| !matches!(a, GenericArgument::Lifetime(_)))
+                    .collect(),
+                _ => Vec::new(),
+            },
+            _ => {
+                let msg = format!("`impl_for` takes instantiations of `{}`", ast.ident);
+                return Err(syn::Error::new_spanned(ty, msg));
+            }
+        };
+        if args.len() != params.len() {
+            let msg = format!(
+                "expected {} generic arguments other than lifetimes, found {}",
+                params.len(),
+                args.len()
+            );
+            return Err(syn::Error::new_spanned(ty, msg));
+        }
+        let mut aliases = TokenStream::new();
+        for (param, arg) in params.iter().zip(args) {
+            aliases.extend(match param {
+                GenericParam::Type(p) => {
+                    let ident = &p.ident;
+                    quote! { type #ident = #arg; }
+                }
+                GenericParam::Const(p) => {
+                    let (ident, const_ty) = (&p.ident, &p.ty);
+                    quote! { const #ident: #const_ty = #arg; }
+                }
+                GenericParam::Lifetime(_) => unreachable!(),
+            });
+        }
+        out.push((ty.clone(), aliases));
+    }
+    Ok(out)
+}
+
+/// The inherent `items` for an instantiation of `impl_for`. Associated items can't be aliased, so
+/// the parameters are substituted in the signatures and the types of consts, with `aliases` in
+/// their bodies.
+fn instantiate_items(items: &TokenStream, aliases: &TokenStream) -> syn::Result<TokenStream> {
+    if aliases.is_empty() {
+        return Ok(items.clone());
+    }
+    let stmts = syn::parse2::<syn::Block>(quote! {{ #aliases }})?.stmts;
+    let params: Vec<(Ident, TokenStream)> = stmts
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::Item(Item::Type(t)) => Some((t.ident.clone(), t.ty.to_token_stream())),
+            Stmt::Item(Item::Const(c)) => Some((c.ident.clone(), c.expr.to_token_stream())),
+            _ => None,
+        })
+        .collect();
+    let mut imp: syn::ItemImpl = syn::parse2(quote! { impl __Rustifact { #items } })?;
+    for item in &mut imp.items {
+        match item {
+            ImplItem::Fn(f) => {
+                f.sig = syn::parse2(substitute(f.sig.to_token_stream(), &params))?;
+                f.block.stmts.splice(0..0, stmts.iter().cloned());
+            }
+            ImplItem::Const(c) => {
+                c.ty = syn::parse2(substitute(c.ty.to_token_stream(), &params))?;
+                let expr = &c.expr;
+                c.expr = syn::parse_quote! {{ #aliases #expr }};
+            }
+            _ => {}
+        }
+    }
+    Ok(imp.items.iter().map(ToTokens::to_token_stream).collect())
+}
+
+/// `toks` with each of the identifiers of `params` replaced by its tokens.
+fn substitute(toks: TokenStream, params: &[(Ident, TokenStream)]) -> TokenStream {
+    let mut out = TokenStream::new();
+    for tt in toks {
+        match tt {
+            TokenTree::Ident(id) => match params.iter().find(|(param, _)| *param == id) {
+                Some((_, arg)) => out.extend(arg.clone()),
+                None => out.extend([TokenTree::Ident(id)]),
+            },
+            TokenTree::Group(g) => {
+                let mut group = Group::new(g.delimiter(), substitute(g.stream(), params));
+                group.set_span(g.span());
+                out.extend([TokenTree::Group(group)]);
+            }
+            tt => out.extend([tt]),
+        }
+    }
+    out
+}
+
+/// The fields of a struct or union, or of all variants of an enum.
+fn all_fields(data: &Data) -> Vec<&Field> {
+    match data {
+        Data::Struct(data) => data.fields.iter().collect(),
+        Data::Enum(data) => data.variants.iter().flat_map(|v| v.fields.iter()).collect(),
+        Data::Union(data) => data.fields.named.iter().collect(),
+    }
+}
+
+/// The inherent items of a type marked `version = N`: its `RUSTIFACT_VERSION` const, and
+/// `version_items`, given the items recording the version alongside an artifact.
+fn version_toks(in_type: &Ident, version: u32) -> TokenStream {
+    let [name_version, check, msg] =
+        ["name_version", "check", "msg"].map(|n| Ident::new(n, Span::call_site()));
+    quote! {
+        /// The schema version of the type, given by `#[rustifact(version = N)]`.
+        pub const RUSTIFACT_VERSION: u32 = #version;
+
+        /// Items to write alongside the artifact `name`: the const `{name}_VERSION`, and the
+        /// `const fn check_{name}_version(expected: u32)`, which panics unless the artifact was
+        /// generated from the `expected` version of the type.
+        pub fn version_items(name: &str) -> rustifact::internal::TokenStream {
+            let #name_version = rustifact::internal::format_ident!("{}_VERSION", name);
+            let #check =
+                rustifact::internal::format_ident!("check_{}_version", name.to_lowercase());
+            let #msg = format!(
+                "rustifact: artifact {} was generated from version {} of {}, not the one expected",
+                name,
+                #version,
+                stringify!(#in_type)
+            );
+            rustifact::internal::quote! {
+                pub const ##name_version: u32 = #version;
+
+                pub const fn ##check(expected: u32) {
+                    if expected != ##name_version {
+                        panic!(##msg);
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -124,7 +815,16 @@ fn get_enum_body(out_type: &Ident, data: &DataEnum) -> TokenStream {
 /// In the case that some components change type under ToTokenStream, like for example String which is
 /// mapped to &'static str, a separate output type may be specified with the OutType attribute.
 ///
-/// #
+/// Fields are mapped by the syntax of their types, which is the same for types substituted into
+/// `macro_rules!` definitions, so types generated by declarative macros derive like hand-written
+/// ones.
+///
+/// The out type may be given generic arguments, as in `#[OutType(View<'static>)]`, for runtime
+/// types borrowing from the artifact.
+///
+/// For enums, values are matched by the variants of the type itself, and each is emitted as the
+/// variant of the same name of the out type, with unit, tuple and named fields alike.
+///
 /// ```no_run
 /// use rustifact::ToTokenStream;
 ///
@@ -138,34 +838,996 @@ fn get_enum_body(out_type: &Ident, data: &DataEnum) -> TokenStream {
 ///     pub s: String,
 /// }
 /// ````
-#[proc_macro_derive(ToTokenStream, attributes(OutType))]
+///
+/// # Attributes
+/// Each attribute is described in the [attribute reference](crate#attribute-reference) of the
+/// crate documentation:
+///
+/// * [Out field shapes](crate#out-field-shapes)
+/// * [Out type paths](crate#out-type-paths)
+/// * [Emission context](crate#emission-context)
+/// * [Generic types](crate#generic-types)
+/// * [Encoded emission](crate#encoded-emission)
+/// * [Interoperating with databake](crate#interoperating-with-databake)
+/// * [Field converters](crate#field-converters)
+/// * [Non-zero integers](crate#non-zero-integers)
+/// * [Atomics](crate#atomics)
+/// * [Interior mutability](crate#interior-mutability)
+/// * [Lazily derived caches](crate#lazily-derived-caches)
+/// * [Ranges](crate#ranges)
+/// * [Unwrapped options](crate#unwrapped-options)
+/// * [Results](crate#results)
+/// * [Network addresses](crate#network-addresses)
+/// * [UUIDs](crate#uuids)
+/// * [Integer radix](crate#integer-radix)
+/// * [Bit flags](crate#bit-flags)
+/// * [Dates and times](crate#dates-and-times)
+/// * [Exact floating point](crate#exact-floating-point)
+/// * [Unsuffixed numbers](crate#unsuffixed-numbers)
+/// * [Pointer-sized integers](crate#pointer-sized-integers)
+/// * [Owned output](crate#owned-output)
+/// * [Other string types](crate#other-string-types)
+/// * [Borrowed values](crate#borrowed-values)
+/// * [Weak references](crate#weak-references)
+/// * [Struct update syntax](crate#struct-update-syntax)
+/// * [Arrays](crate#arrays)
+/// * [Tuples](crate#tuples)
+/// * [Boxes](crate#boxes)
+/// * [Function pointers](crate#function-pointers)
+/// * [Sorted collections](crate#sorted-collections)
+/// * [Maps as slices](crate#maps-as-slices)
+/// * [Multimaps](crate#multimaps)
+/// * [Prefix tries](crate#prefix-tries)
+/// * [State machines](crate#state-machines)
+/// * [Sets](crate#sets)
+/// * [Iterators](crate#iterators)
+/// * [Run-length encoded flags](crate#run-length-encoded-flags)
+/// * [Packed bools](crate#packed-bools)
+/// * [Static references](crate#static-references)
+/// * [Arenas](crate#arenas)
+/// * [Runtime initialization](crate#runtime-initialization)
+/// * [Chunked collections](crate#chunked-collections)
+/// * [Length constraints](crate#length-constraints)
+/// * [Padded collections](crate#padded-collections)
+/// * [Sidecar files](crate#sidecar-files)
+/// * [Feature-gated fields](crate#feature-gated-fields)
+/// * [Minimum Rust versions](crate#minimum-rust-versions)
+/// * [Build-side impls](crate#build-side-impls)
+/// * [Bindgen-generated types](crate#bindgen-generated-types)
+/// * [Validation](crate#validation)
+/// * [Schema versions](crate#schema-versions)
+/// * [Size limits](crate#size-limits)
+/// * [Artifact groups](crate#artifact-groups)
+/// * [Writer method](crate#writer-method)
+/// * [Checksums](crate#checksums)
+/// * [Size metrics](crate#size-metrics)
+/// * [Keyed values](crate#keyed-values)
+/// * [Streaming emission](crate#streaming-emission)
+/// * [Variant names](crate#variant-names)
+/// * [Listing variants](crate#listing-variants)
+/// * [Non-exhaustive enums](crate#non-exhaustive-enums)
+/// * [Catch-all variants](crate#catch-all-variants)
+/// * [Variant naming conventions](crate#variant-naming-conventions)
+/// * [Debugging expansions](crate#debugging-expansions)
+/// * [Reproducible output](crate#reproducible-output)
+#[proc_macro_derive(ToTokenStream, attributes(OutType, OutFields, rustifact))]
 pub fn derive_token_stream(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let ast = parse_macro_input!(input as DeriveInput);
+    let mut ast = parse_macro_input!(input as DeriveInput);
+    normalize_self(&mut ast);
     let in_type = &ast.ident;
-    let mut out_type: Ident = in_type.clone();
+    let attrs = match ContainerAttrs::from_attrs(&ast.attrs) {
+        Ok(attrs) => attrs,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let out_type = attrs.out_type.clone().unwrap_or_else(|| in_type.clone());
+    let out_path_binding = out_path_binding(&out_type, &attrs);
+    if let Some(packed) = &attrs.bitpack {
+        if !matches!(
+            &ast.data,
+            Data::Struct(DataStruct {
+                fields: Fields::Named(_),
+                ..
+            })
+        ) {
+            let msg = "`bitpack` is only supported on structs with named fields";
+            return syn::Error::new_spanned(packed, msg)
+                .to_compile_error()
+                .into();
+        }
+    }
+    if let Some(base) = &attrs.base {
+        if !matches!(
+            &ast.data,
+            Data::Struct(DataStruct {
+                fields: Fields::Named(_),
+                ..
+            })
+        ) {
+            let msg = "`base` is only supported on structs with named fields";
+            return syn::Error::new_spanned(base, msg).to_compile_error().into();
+        }
+    }
+    let body = match (&attrs.encode, &ast.data) {
+        (Some(_), _) if attrs.with.is_some() => Err(syn::Error::new_spanned(
+            attrs.with.as_ref(),
+            "only one of `encode` and `with` may be given",
+        )),
+        (None, _) if attrs.compress.is_some() => Err(syn::Error::new_spanned(
+            in_type,
+            "`compress` requires one of `encode` and `encoder`",
+        )),
+        (None, _) if attrs.with.is_some() => {
+            let with = &attrs.with;
+            Ok(quote! { #with::to_toks(self, toks); })
+        }
+        (Some(encoding), _) => Ok(get_encoded_body(encoding, &attrs)),
+        (None, Data::Struct(_)) if attrs.arena.is_some() => Ok(quote! {
+            toks.extend(Self::emit_arena(::core::slice::from_ref(self)));
+        }),
+        (None, data) if attrs.as_str => get_as_str_body(in_type, data, &attrs),
+        (None, Data::Struct(data)) => get_struct_body(&out_type, data, &attrs),
+        (None, Data::Enum(data)) => get_enum_body(in_type, &out_type, data, &attrs),
+        (None, Data::Union(data)) => get_union_body(&out_type, data, &attrs),
+    };
+    let body = match (body, validate_toks(&ast, &attrs)) {
+        (Ok(body), Ok(validate)) => quote! { #validate #body },
+        (Err(e), _) | (_, Err(e)) => return e.to_compile_error().into(),
+    };
+    let generics = bound::with_bounds(&ast.generics, &ast.data);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut inherent = TokenStream::new();
+    if let Some(version) = attrs.version {
+        inherent.extend(version_toks(in_type, version));
+    }
+    let body = match attrs.max_tokens {
+        // Tokens are counted as they're printed, which separates most with spaces.
+        Some(max) => {
+            let inner = Ident::new("inner", Span::call_site());
+            let what = in_type.to_string();
+            quote! {
+                let mut #inner = rustifact::internal::TokenStream::new();
+                {
+                    let toks = &mut #inner;
+                    #body
+                }
+                let count = #inner.to_string().split_whitespace().count();
+                if count > #max {
+                    ::core::panic!(
+                        "rustifact: a {} emitted about {} tokens, more than max_tokens = {}",
+                        #what,
+                        count,
+                        #max
+                    );
+                }
+                toks.extend(#inner);
+            }
+        }
+        None => body,
+    };
+    // With a context, the body is that of `to_toks_with`, which `to_toks` calls with the default.
+    let body = match &attrs.context {
+        Some(_) if attrs.arena.is_some() || has_field(&ast.data, |a| a.hoist.is_some()) => {
+            let msg = "`context` is not supported with `arena` or `hoist`";
+            return syn::Error::new_spanned(in_type, msg)
+                .to_compile_error()
+                .into();
+        }
+        Some(ctx) => {
+            inherent.extend(quote! {
+                /// Extends `toks` with the tokens of `self`, emitted within `ctx`, which is passed
+                /// on to the fields marked `context` or `with_context`.
+                pub fn to_toks_with(&self, ctx: &#ctx, toks: &mut rustifact::internal::TokenStream) {
+                    let __rustifact_ctx = ctx;
+                    #out_path_binding
+                    #body
+                }
+            });
+            quote! {
+                self.to_toks_with(&<#ctx as ::core::default::Default>::default(), toks);
+            }
+        }
+        None if has_field(&ast.data, |a| a.context || a.with_context.is_some()) => {
+            let msg = "fields marked `context` or `with_context` require `context` on the type";
+            return syn::Error::new_spanned(in_type, msg)
+                .to_compile_error()
+                .into();
+        }
+        None => body,
+    };
+    // With hoisted fields, the body pushes their statics to an items stream, which the writer
+    // methods emit alongside the value, and `to_toks` into a block around it.
+    let hoist = has_field(&ast.data, |a| a.hoist.is_some());
+    let body = match hoist {
+        true if attrs.arena.is_some() => {
+            let msg = "`hoist` is not supported with `arena`";
+            return syn::Error::new_spanned(in_type, msg)
+                .to_compile_error()
+                .into();
+        }
+        true => {
+            let prefix = out_type.to_string().to_uppercase();
+            // Statics can't be referenced from consts before 1.83, as when within `write_const!`.
+            let constant = !attrs.supports(83);
+            let (inner, hoisted) = (
+                Ident::new("inner", Span::call_site()),
+                Ident::new("hoisted", Span::call_site()),
+            );
+            inherent.extend(quote! {
+                /// Extends `toks` with the tokens of `self`, which reference its hoisted fields by
+                /// name, and `hoisted` with the items defining them, named after `prefix` and the
+                /// field as in `__CONFIG_LEVELS`. The items are `const`s if `constant` is set, as
+                /// for use in a const, and otherwise `static`s.
+                pub fn to_toks_hoisted(
+                    &self,
+                    prefix: &str,
+                    constant: bool,
+                    toks: &mut rustifact::internal::TokenStream,
+                    hoisted: &mut rustifact::internal::TokenStream,
+                ) {
+                    let (__rustifact_prefix, __rustifact_const, __rustifact_hoisted) =
+                        (prefix, constant, hoisted);
+                    #out_path_binding
+                    #body
+                }
+            });
+            quote! {
+                let mut #inner = rustifact::internal::TokenStream::new();
+                let mut #hoisted = rustifact::internal::TokenStream::new();
+                self.to_toks_hoisted(#prefix, #constant, &mut #inner, &mut #hoisted);
+                toks.extend(rustifact::internal::quote! { { ##hoisted ##inner } });
+            }
+        }
+        false => body,
+    };
+    if attrs.fingerprint {
+        let fingerprint = fingerprint::fingerprint(&ast);
+        inherent.extend(quote! { pub const RUSTIFACT_FINGERPRINT: u64 = #fingerprint; });
+    }
+    if attrs.bitpack.is_some() {
+        let (bools, bits_ty) = match bitpack_fields(all_fields(&ast.data)) {
+            Ok(packed) => packed,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        for (i, ident) in bools.into_iter().enumerate() {
+            let name = format_ident!("{}_BIT", ident.to_string().to_uppercase());
+            let doc = format!(
+                "The bit of `{}` in the packed bools of the out type.",
+                ident
+            );
+            inherent.extend(quote! {
+                #[doc = #doc]
+                pub const #name: #bits_ty = 1 << #i;
+            });
+        }
+    }
+    if let (Some(id), Data::Struct(data)) = (&attrs.arena, &ast.data) {
+        match arena::arena_method(&out_type, data, &attrs, id, in_type) {
+            Ok(method) => inherent.extend(method),
+            Err(e) => return e.to_compile_error().into(),
+        }
+    }
+    if attrs.all_variants {
+        let variants = match &ast.data {
+            Data::Enum(data) if data.variants.iter().all(|v| v.fields.is_empty()) => &data.variants,
+            _ => {
+                let msg = "`all_variants` is only supported on enums without fields";
+                return syn::Error::new_spanned(in_type, msg)
+                    .to_compile_error()
+                    .into();
+            }
+        };
+        let idents = variants.iter().map(|v| &v.ident);
+        let (elems, v) = (
+            Ident::new("elems", Span::call_site()),
+            Ident::new("v", Span::call_site()),
+        );
+        inherent.extend(quote! {
+            /// Every variant, in order of declaration.
+            pub const ALL: &'static [Self] = &[#(Self::#idents),*];
+
+            /// The tokens of `ALL` as a slice expression of the out type.
+            pub fn emit_all() -> rustifact::internal::TokenStream {
+                let mut #elems = rustifact::internal::TokenStream::new();
+                for #v in Self::ALL {
+                    let #v = rustifact::ToTokenStream::to_tok_stream(#v);
+                    #elems.extend(rustifact::internal::quote! { ##v, });
+                }
+                rustifact::internal::quote! { &[##elems] }
+            }
+        });
+    }
+    if let Some(group) = &attrs.group {
+        inherent.extend(quote! { pub const RUSTIFACT_GROUP: &str = #group; });
+    }
+    if let Some(key) = &attrs.key {
+        let field = match named_field(&ast.data, key) {
+            Ok(field) => field,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        let key_ty = &field.ty;
+        inherent.extend(quote! {
+            /// The tokens of the key field.
+            pub fn key_tokens(&self) -> rustifact::internal::TokenStream {
+                rustifact::ToTokenStream::to_tok_stream(&self.#key)
+            }
 
-    for attr in &ast.attrs {
-        if attr.path().is_ident("OutType") {
-            if let Ok(id) = attr.parse_args::<Ident>() {
-                out_type = id;
+            /// The key paired with the value, as taken by the `entry` methods of rustifact's map
+            /// builders.
+            pub fn keyed(&self) -> (#key_ty, &Self) {
+                (::core::clone::Clone::clone(&self.#key), self)
+            }
+        });
+    }
+    // Generated code shouldn't trip the lints of crates using the derive. The generic parameters of
+    // methods are prefixed so as not to clash with those of the type.
+    let allow = quote! {
+        #[automatically_derived]
+        #[allow(
+            unused_variables,
+            unused_qualifications,
+            clippy::needless_borrow,
+            clippy::redundant_clone,
+            clippy::let_and_return,
+            clippy::unit_arg,
+            clippy::unused_unit
+        )]
+    };
+    if has_field(&ast.data, |a| a.sparse) {
+        inherent.extend(quote! {
+            /// The value of `key` in `entries`, as emitted for fields marked
+            /// `#[rustifact(sparse)]`, or `default` if it was omitted.
+            pub fn rustifact_sparse_get<'rustifact, RustifactK: ::core::cmp::Ord, RustifactV>(
+                entries: &'rustifact [(RustifactK, RustifactV)],
+                key: &RustifactK,
+                default: &'rustifact RustifactV,
+            ) -> &'rustifact RustifactV {
+                match entries.binary_search_by(|(k, _)| k.cmp(key)) {
+                    Ok(i) => &entries[i].1,
+                    Err(_) => default,
+                }
+            }
+        });
+    }
+    if has_field(&ast.data, |a| a.lookup) {
+        inherent.extend(quote! {
+            /// The value of `key` in `entries`, as emitted for fields marked
+            /// `#[rustifact(lookup = "binary_search")]`.
+            pub fn rustifact_lookup_get<'rustifact, RustifactK, RustifactQ, RustifactV>(
+                entries: &'rustifact [(RustifactK, RustifactV)],
+                key: &RustifactQ,
+            ) -> ::core::option::Option<&'rustifact RustifactV>
+            where
+                RustifactK: ::core::borrow::Borrow<RustifactQ>,
+                RustifactQ: ::core::cmp::Ord + ?::core::marker::Sized,
+            {
+                let i = entries.binary_search_by_key(&key, |(k, _)| k.borrow()).ok()?;
+                ::core::option::Option::Some(&entries[i].1)
+            }
+        });
+    }
+    let mut sidecar_elems: Vec<syn::Path> = Vec::new();
+    for f in all_fields(&ast.data) {
+        if !FieldAttrs::from_attrs(&f.attrs).is_ok_and(|a| a.sidecar) {
+            continue;
+        }
+        if let Some(elem) = emit::sidecar_elem(&f.ty) {
+            if !sidecar_elems
+                .iter()
+                .any(|e| e.is_ident(elem.get_ident().unwrap()))
+            {
+                sidecar_elems.push(elem);
             }
         }
     }
-    let body = match &ast.data {
-        Data::Struct(data) => get_struct_body(&out_type, data),
-        Data::Enum(data) => get_enum_body(&out_type, data),
-        Data::Union(_) => {
-            panic!("Unions are not yet supported");
+    for elem in &sidecar_elems {
+        let get = format_ident!("rustifact_sidecar_get_{}", elem.get_ident().unwrap());
+        inherent.extend(quote! {
+            /// The `index`th number of `bytes`, as included for fields marked
+            /// `#[rustifact(sidecar)]`.
+            pub fn #get(bytes: &[u8], index: usize) -> ::core::option::Option<#elem> {
+                const SIZE: usize = ::core::mem::size_of::<#elem>();
+                let b = bytes.get(index * SIZE..(index + 1) * SIZE)?;
+                ::core::option::Option::Some(#elem::from_le_bytes(<[u8; SIZE]>::try_from(b).ok()?))
+            }
+        });
+    }
+    if has_field(&ast.data, |a| a.multimap) {
+        inherent.extend(quote! {
+            /// The values of `key` in `entries`, as emitted for fields marked
+            /// `#[rustifact(multimap)]`, or an empty slice if it has none.
+            pub fn rustifact_multimap_get<'rustifact, RustifactK: ::core::cmp::Ord, RustifactV>(
+                entries: &[(RustifactK, &'rustifact [RustifactV])],
+                key: &RustifactK,
+            ) -> &'rustifact [RustifactV] {
+                match entries.binary_search_by(|(k, _)| k.cmp(key)) {
+                    Ok(i) => entries[i].1,
+                    Err(_) => &[],
+                }
+            }
+        });
+    }
+    if has_field(&ast.data, |a| a.trie) {
+        inherent.extend(quote! {
+            /// The value of the longest key of `nodes` that's a prefix of `key`, with its length,
+            /// as emitted for fields marked `#[rustifact(trie)]`.
+            pub fn rustifact_trie_longest_prefix<'rustifact, RustifactV>(
+                nodes: &'rustifact [(&'rustifact [(u8, u32)], ::core::option::Option<RustifactV>)],
+                key: &str,
+            ) -> ::core::option::Option<(usize, &'rustifact RustifactV)> {
+                let mut node = nodes.first()?;
+                let mut best = node.1.as_ref().map(|v| (0, v));
+                for (i, b) in key.bytes().enumerate() {
+                    match node.0.binary_search_by_key(&b, |e| e.0) {
+                        Ok(j) => node = nodes.get(node.0[j].1 as usize)?,
+                        Err(_) => break,
+                    }
+                    if let ::core::option::Option::Some(v) = &node.1 {
+                        best = ::core::option::Option::Some((i + 1, v));
+                    }
+                }
+                best
+            }
+        });
+    }
+    if has_field(&ast.data, |a| a.chunk.is_some()) {
+        inherent.extend(quote! {
+            /// The element at `index` of `chunks`, as emitted for fields marked
+            /// `#[rustifact(chunk = N)]`.
+            pub fn rustifact_chunk_get<'rustifact, RustifactT>(
+                chunks: &[&'rustifact [RustifactT]],
+                index: usize,
+            ) -> ::core::option::Option<&'rustifact RustifactT> {
+                let size = chunks.first()?.len();
+                chunks.get(index / size)?.get(index % size)
+            }
+        });
+    }
+    if has_field(&ast.data, |a| a.runs) {
+        inherent.extend(quote! {
+            /// Whether `index` lies within one of `runs`, as emitted for fields marked
+            /// `#[rustifact(runs)]`.
+            pub const fn rustifact_runs_contain(runs: &[(u32, u32)], index: u32) -> bool {
+                let (mut lo, mut hi) = (0, runs.len());
+                while lo < hi {
+                    let mid = lo + (hi - lo) / 2;
+                    let (start, end) = runs[mid];
+                    if index < start {
+                        hi = mid;
+                    } else if index >= end {
+                        lo = mid + 1;
+                    } else {
+                        return true;
+                    }
+                }
+                false
+            }
+        });
+    }
+    if attrs.stream {
+        inherent.extend(quote! {
+            /// The tokens of each element of `items` in turn, computed lazily.
+            pub fn emit_elements(
+                items: &[Self],
+            ) -> impl ::core::iter::Iterator<Item = rustifact::internal::TokenStream> + '_ {
+                items.iter().map(rustifact::ToTokenStream::to_tok_stream)
+            }
+
+            /// Writes `items` to `out` as the source of an array expression, one element at a
+            /// time, so that the tokens of the whole array are never held in memory at once.
+            pub fn write_elements<RustifactW: ::std::io::Write>(
+                items: &[Self],
+                out: &mut RustifactW,
+            ) -> ::std::io::Result<()> {
+                out.write_all(b"[")?;
+                for toks in Self::emit_elements(items) {
+                    ::std::write!(out, "{},", toks)?;
+                }
+                out.write_all(b"]")
+            }
+        });
+    }
+    if attrs.bulk {
+        let elems = Ident::new("elems", Span::call_site());
+        inherent.extend(quote! {
+            /// The tokens of `items` as a single slice expression, built in one pass into a shared
+            /// stream rather than through a separate stream for each element.
+            pub fn emit_slice(items: &[Self]) -> rustifact::internal::TokenStream {
+                let comma = rustifact::internal::quote! { , };
+                let mut #elems = rustifact::internal::TokenStream::new();
+                for item in items {
+                    rustifact::ToTokenStream::to_toks(item, &mut #elems);
+                    #elems.extend(comma.clone());
+                }
+                rustifact::internal::quote! { &[##elems] }
+            }
+        });
+    }
+    // FNV-1a over the printed tokens of `data`, as with the fingerprint of the type.
+    let data = Ident::new("data", Span::call_site());
+    let checksum_of = quote! {{
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for b in #data.to_string().bytes() {
+            hash ^= b as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
         }
+        hash
+    }};
+    // As counted for `max_tokens`.
+    let approx_tokens_of = quote! { #data.to_string().split_whitespace().count() };
+    if attrs.metrics {
+        let [count, approx, count_id, approx_id] =
+            ["count", "approx", "count_id", "approx_id"].map(|n| Ident::new(n, Span::call_site()));
+        inherent.extend(quote! {
+            /// The consts `NAME_ENTRY_COUNT` and `NAME_APPROX_TOKENS`, the number of `items` and
+            /// about how many tokens they emit, for the table of them named `name`.
+            pub fn emit_metrics(items: &[Self], name: &str) -> rustifact::internal::TokenStream {
+                let #count_id = rustifact::internal::format_ident!("{}_ENTRY_COUNT", name);
+                let #approx_id = rustifact::internal::format_ident!("{}_APPROX_TOKENS", name);
+                let #count = items.len();
+                let #approx: usize = items
+                    .iter()
+                    .map(|item| {
+                        let #data = rustifact::ToTokenStream::to_tok_stream(item);
+                        #approx_tokens_of
+                    })
+                    .sum();
+                rustifact::internal::quote! {
+                    pub const ##count_id: usize = ##count;
+                    pub const ##approx_id: usize = ##approx;
+                }
+            }
+        });
+    }
+    if attrs.checksum {
+        inherent.extend(quote! {
+            /// A hash of the tokens emitted for `self`, for checking an artifact matches what the
+            /// build produced.
+            pub fn checksum(&self) -> u64 {
+                let #data = rustifact::ToTokenStream::to_tok_stream(self);
+                #checksum_of
+            }
+        });
+    }
+    if attrs.writer {
+        if !generics.params.is_empty() {
+            let msg =
+                "`writer` isn't supported on generic types, use rustifact's write macros instead";
+            return syn::Error::new_spanned(&ast.generics, msg)
+                .to_compile_error()
+                .into();
+        }
+        let id = Ident::new("id", Span::call_site());
+        let out_path = out_path_toks(&out_type, &attrs);
+        let version_items = match attrs.version {
+            Some(_) => quote! {
+                let mut tokens = tokens;
+                tokens.extend(Self::version_items(name));
+            },
+            None => TokenStream::new(),
+        };
+        // The statics of hoisted fields are emitted as items before the artifact, as consts
+        // within a const.
+        let hoisted = Ident::new("hoisted", Span::call_site());
+        let data_of = |constant: bool| match hoist {
+            true => quote! {
+                let mut #data = rustifact::internal::TokenStream::new();
+                let mut #hoisted = rustifact::internal::TokenStream::new();
+                self.to_toks_hoisted(name, #constant, &mut #data, &mut #hoisted);
+            },
+            false => quote! { let #data = rustifact::ToTokenStream::to_tok_stream(self); },
+        };
+        let hoisted = match hoist {
+            true => quote! { ##hoisted },
+            false => TokenStream::new(),
+        };
+        let checksum = if attrs.checksum {
+            let sum = Ident::new("sum", Span::call_site());
+            let sum_id = Ident::new("sum_id", Span::call_site());
+            quote! {
+                let #sum: u64 = #checksum_of;
+                let #sum_id = rustifact::internal::format_ident!("{}_CHECKSUM", name);
+                let mut tokens = tokens;
+                tokens.extend(rustifact::internal::quote! { pub const ##sum_id: u64 = ##sum; });
+            }
+        } else {
+            TokenStream::new()
+        };
+        let metrics = if attrs.metrics {
+            let approx = Ident::new("approx", Span::call_site());
+            let approx_id = Ident::new("approx_id", Span::call_site());
+            quote! {
+                let #approx: usize = #approx_tokens_of;
+                let #approx_id = rustifact::internal::format_ident!("{}_APPROX_TOKENS", name);
+                let mut tokens = tokens;
+                tokens.extend(rustifact::internal::quote! { pub const ##approx_id: usize = ##approx; });
+            }
+        } else {
+            TokenStream::new()
+        };
+        let vis = Ident::new("vis", Span::call_site());
+        // The version items follow the item, as with `write_artifact`.
+        let versioned = Ident::new("versioned", Span::call_site());
+        let (version_of, version) = match attrs.version {
+            Some(_) => (
+                quote! { let #versioned = Self::version_items(name); },
+                quote! { ##versioned },
+            ),
+            None => (TokenStream::new(), TokenStream::new()),
+        };
+        for (method, kind, constant) in [
+            ("emit_static", quote! { static }, false),
+            ("emit_const", quote! { const }, true),
+        ] {
+            let data_toks = data_of(constant);
+            let method = Ident::new(method, Span::call_site());
+            let doc = format!(
+                "The item `{} NAME: Out = ...;` defining `self` as `name`, with visibility such as \
+                 `\"pub\"` or `\"pub(crate)\"`, or `\"\"` for private.",
+                kind
+            );
+            inherent.extend(quote! {
+                #[doc = #doc]
+                pub fn #method(&self, name: &str, visibility: &str) -> rustifact::internal::TokenStream {
+                    let #id = rustifact::internal::format_ident!("{}", name);
+                    let #vis: rustifact::internal::TokenStream =
+                        visibility.parse().expect("rustifact: invalid visibility");
+                    #data_toks
+                    #out_path_binding
+                    #version_of
+                    rustifact::internal::quote! {
+                        #hoisted ##vis #kind ##id: #out_path = ##data; #version
+                    }
+                }
+            });
+        }
+        let data_toks = data_of(false);
+        inherent.extend(quote! {
+            /// Writes `self` as the static `name`, for import with `rustifact::use_symbols!`.
+            pub fn write_artifact(&self, name: &str) {
+                let #id = rustifact::internal::format_ident!("{}", name);
+                #data_toks
+                #out_path_binding
+                let tokens =
+                    rustifact::internal::quote! { #hoisted static ##id: #out_path = ##data; };
+                #version_items
+                #checksum
+                #metrics
+                // The path from which `use_symbols!` includes the symbol.
+                let path = ::std::format!(
+                    "{}/rustifact_{}_{}.rs",
+                    ::std::env::var("OUT_DIR").expect("rustifact: OUT_DIR is not set"),
+                    ::std::env::var("CARGO_PKG_NAME").expect("rustifact: CARGO_PKG_NAME is not set"),
+                    name,
+                );
+                let file = rustifact::internal::parse_file(&tokens.to_string())
+                    .expect("rustifact: failed to parse the tokens of the artifact");
+                ::std::fs::write(&path, rustifact::internal::unparse(&file))
+                    .expect("rustifact: failed to write the artifact");
+            }
+        });
+    }
+    // Each impl is either generic over the parameters of the type or, with `impl_for`, for one of
+    // its instantiations, generic only over its lifetimes.
+    let impls: Vec<(TokenStream, TokenStream, TokenStream, TokenStream)> = match &attrs.impl_for {
+        Some(types) => {
+            let instantiations = match instantiations(&ast, types) {
+                Ok(instantiations) => instantiations,
+                Err(e) => return e.to_compile_error().into(),
+            };
+            let lifetimes: Vec<&syn::LifetimeParam> = ast.generics.lifetimes().collect();
+            let impl_generics = if lifetimes.is_empty() {
+                TokenStream::new()
+            } else {
+                quote! { <#(#lifetimes),*> }
+            };
+            instantiations
+                .into_iter()
+                .map(|(ty, aliases)| {
+                    (
+                        impl_generics.clone(),
+                        quote! { #ty },
+                        TokenStream::new(),
+                        aliases,
+                    )
+                })
+                .collect()
+        }
+        None => vec![(
+            quote! { #impl_generics },
+            quote! { #in_type #ty_generics },
+            quote! { #where_clause },
+            TokenStream::new(),
+        )],
     };
-    let generics = &ast.generics;
-    let gen_where = &generics.where_clause;
-    quote! {
-        impl #generics rustifact::ToTokenStream for #in_type #generics #gen_where {
-            fn to_toks(&self, toks: &mut rustifact::internal::TokenStream) {
-                #body
+    let mut items = TokenStream::new();
+    for (impl_generics, self_ty, where_clause, aliases) in impls {
+        items.extend(quote! {
+            #allow
+            impl #impl_generics rustifact::ToTokenStream for #self_ty #where_clause {
+                fn to_toks(&self, toks: &mut rustifact::internal::TokenStream) {
+                    #aliases
+                    #out_path_binding
+                    #body
+                }
             }
+        });
+        if attrs.bake {
+            items.extend(quote! {
+                #allow
+                impl #impl_generics ::databake::Bake for #self_ty #where_clause {
+                    fn bake(&self, _: &::databake::CrateEnv) -> rustifact::internal::TokenStream {
+                        rustifact::ToTokenStream::to_tok_stream(self)
+                    }
+                }
+            });
+        }
+        if !inherent.is_empty() {
+            let inherent = match instantiate_items(&inherent, &aliases) {
+                Ok(inherent) => inherent,
+                Err(e) => return e.to_compile_error().into(),
+            };
+            items.extend(quote! {
+                #allow
+                impl #impl_generics #self_ty #where_clause {
+                    #inherent
+                }
+            });
+        }
+    }
+    if attrs.chain {
+        match chain_impl(&out_type, &attrs) {
+            Ok(chain) => items.extend(quote! { #allow #chain }),
+            Err(e) => return e.to_compile_error().into(),
         }
     }
+    let cfg = attrs.impl_cfg.as_ref().map(|cfg| quote! { #[cfg(#cfg)] });
+    // The impls are wrapped in an anonymous const, so any helper items they grow stay out of the
+    // namespace and docs of the user's crate.
+    let expanded = quote! {
+        #cfg
+        #[doc(hidden)]
+        const _: () = {
+            #items
+        };
+    };
+    if debug_dump(in_type) {
+        eprintln!("rustifact_derive: expansion of {}:\n{}", in_type, expanded);
+    }
+    expanded.into()
+}
+
+/// Whether `RUSTIFACT_DERIVE_DEBUG` asks for the expansion of `ty` to be printed, being `1` for all
+/// types, or else a comma-separated list of type names.
+fn debug_dump(ty: &Ident) -> bool {
+    match std::env::var("RUSTIFACT_DERIVE_DEBUG") {
+        Ok(filter) if filter == "1" => true,
+        Ok(filter) => filter.split(',').any(|name| *ty == name.trim()),
+        Err(_) => false,
+    }
+}
+
+/// Defines the `RustifactEncoder<T>` trait, implemented by the encoders named with
+/// `#[rustifact(encoder = "...")]`, which must be invoked at the root of the crate using the
+/// derive.
+///
+/// ```no_run
+/// use rustifact::internal::{quote, TokenStream};
+/// use rustifact::ToTokenStream;
+///
+/// rustifact_derive::rustifact_encoder_trait!();
+///
+/// #[derive(ToTokenStream)]
+/// #[OutType(Image)]
+/// #[rustifact(encoder = "Raw")]
+/// pub struct ImageIn {
+///     pub pixels: Vec<u8>,
+/// }
+///
+/// pub struct Raw;
+///
+/// impl RustifactEncoder<ImageIn> for Raw {
+///     fn encode(value: &ImageIn) -> Vec<u8> {
+///         value.pixels.clone()
+///     }
+///
+///     fn decoder(bytes: TokenStream) -> TokenStream {
+///         quote! { Image { pixels: #bytes } }
+///     }
+/// }
+/// # fn main() {}
+/// ````
+#[proc_macro]
+pub fn rustifact_encoder_trait(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    if let Some(tt) = TokenStream::from(input).into_iter().next() {
+        return syn::Error::new_spanned(tt, "`rustifact_encoder_trait!` takes no arguments")
+            .to_compile_error()
+            .into();
+    }
+    encoder_trait().into()
+}
+
+/// Asserts that the tokens emitted for a value form a constant of the given out type, for testing
+/// the attributes of a derive without a build script.
+///
+/// `rustifact_assert_emits!(value, OutType)` checks at compile time that `OutType` exists, and at
+/// runtime that `const _: OutType = <tokens>;` parses and type checks, panicking with the errors
+/// and the tokens if not. The constant is compiled by rustc against the library of the package,
+/// with `use <crate>::*;` in scope, so the macro is for the integration tests in `tests/`, and the
+/// out type and the types named by the tokens must be reachable from the root of the library.
+///
+/// ```no_run
+/// use rustifact::ToTokenStream;
+/// use rustifact_derive::rustifact_assert_emits;
+/// use my_crate::Range;
+///
+/// #[derive(ToTokenStream)]
+/// #[OutType(Range)]
+/// pub struct RangeBuild {
+///     pub start: u32,
+///     pub end: u32,
+/// }
+///
+/// #[test]
+/// fn emits_range() {
+///     rustifact_assert_emits!(RangeBuild { start: 1, end: 2 }, Range);
+/// }
+/// # mod my_crate { pub struct Range { pub start: u32, pub end: u32 } }
+/// ````
+#[proc_macro]
+pub fn rustifact_assert_emits(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let parser = |input: syn::parse::ParseStream| {
+        let value: syn::Expr = input.parse()?;
+        input.parse::<syn::Token![,]>()?;
+        let ty: Type = input.parse()?;
+        input.parse::<Option<syn::Token![,]>>()?;
+        Ok((value, ty))
+    };
+    let (value, ty) = match syn::parse::Parser::parse(parser, input) {
+        Ok(parsed) => parsed,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let what = value.to_token_stream().to_string();
+    let ty_name = ty.to_token_stream().to_string();
+    let data = Ident::new("data", Span::call_site());
+    quote! {{
+        let _: ::core::marker::PhantomData<#ty> = ::core::marker::PhantomData;
+        let #data = rustifact::ToTokenStream::to_tok_stream(&(#value));
+        let item = rustifact::internal::quote! { const _: #ty = ##data; };
+        if let ::core::result::Result::Err(e) = rustifact::internal::parse_file(&item.to_string()) {
+            ::core::panic!(
+                "rustifact: the tokens emitted for {} don't form a constant of type {}: {}\n{}",
+                #what,
+                #ty_name,
+                e,
+                #data
+            );
+        }
+        // The library is built alongside the integration tests, in the directory of their
+        // executables.
+        let krate = ::core::env!("CARGO_PKG_NAME").replace('-', "_");
+        let exe = ::std::env::current_exe().expect("rustifact: the test executable isn't known");
+        let deps = exe.parent().expect("rustifact: the test executable has no directory");
+        let prefix = ::std::format!("lib{}-", krate);
+        let lib = ::std::fs::read_dir(deps)
+            .expect("rustifact: failed to read the directory of the test executable")
+            .filter_map(::core::result::Result::ok)
+            .filter(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                name.starts_with(&prefix) && name.ends_with(".rlib")
+            })
+            .max_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok())
+            .expect("rustifact: the library of the package wasn't found, so `rustifact_assert_emits!` must be used in an integration test");
+        let dir = ::std::env::temp_dir().join(::std::format!(
+            "rustifact_assert_emits_{}",
+            ::std::process::id()
+        ));
+        ::std::fs::create_dir_all(&dir).expect("rustifact: failed to create a temporary directory");
+        // Named after the call site, as tests run in parallel.
+        let name = ::std::format!("check_{}_{}", ::core::line!(), ::core::column!());
+        let src = dir.join(::std::format!("{}.rs", name));
+        let check = ::std::format!("#![allow(unused_imports)]\nuse {}::*;\n{}\n", krate, item);
+        ::std::fs::write(&src, check).expect("rustifact: failed to write the check");
+        let rustc = ::std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+        let output = ::std::process::Command::new(rustc)
+            .args(["--edition", "2021", "--crate-type", "lib", "--emit", "metadata"])
+            .arg("--crate-name")
+            .arg(&name)
+            .arg("-L")
+            .arg(::std::format!("dependency={}", deps.display()))
+            .arg("--extern")
+            .arg(::std::format!("{}={}", krate, lib.path().display()))
+            .arg("--out-dir")
+            .arg(&dir)
+            .arg(&src)
+            .output()
+            .expect("rustifact: failed to run rustc");
+        if !output.status.success() {
+            ::core::panic!(
+                "rustifact: the tokens emitted for {} don't type check as a constant of type {}:\n{}\n{}",
+                #what,
+                #ty_name,
+                ::std::string::String::from_utf8_lossy(&output.stderr),
+                #data
+            );
+        }
+    }}
     .into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn instantiations_alias_type_and_const_params() {
+        let ast: DeriveInput = parse_quote! {
+            struct G<'a, T, const N: usize> { a: &'a [T; N] }
+        };
+        let types: Vec<Type> = vec![parse_quote!(G<'a, u8, 3>), parse_quote!(G<'a, u16, 4>)];
+        let out = instantiations(&ast, &types).unwrap();
+        let aliases: Vec<String> = out.iter().map(|(_, a)| a.to_string()).collect();
+        assert_eq!(
+            aliases,
+            [
+                "type T = u8 ; const N : usize = 3 ;",
+                "type T = u16 ; const N : usize = 4 ;"
+            ]
+        );
+    }
+
+    #[test]
+    fn instantiations_check_the_type_and_arity() {
+        let ast: DeriveInput = parse_quote! { struct G<T> { a: T } };
+        let other: Vec<Type> = vec![parse_quote!(H<u8>)];
+        let err = instantiations(&ast, &other).err().unwrap();
+        assert_eq!(err.to_string(), "`impl_for` takes instantiations of `G`");
+        let arity: Vec<Type> = vec![parse_quote!(G<u8, u16>)];
+        let err = instantiations(&ast, &arity).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "expected 1 generic arguments other than lifetimes, found 2"
+        );
+    }
+
+    #[test]
+    fn instantiated_items_substitute_signatures() {
+        let items = quote! {
+            pub const N: [T; 2] = [T::MAX; 2];
+            pub fn keyed(&self) -> (T, &Self) { (self.k, self) }
+        };
+        let aliases = quote! { type T = u8; };
+        let out = instantiate_items(&items, &aliases).unwrap().to_string();
+        assert_eq!(
+            out,
+            quote! {
+                pub const N: [u8; 2] = { type T = u8; [T::MAX; 2] };
+                pub fn keyed(&self) -> (u8, &Self) { type T = u8; (self.k, self) }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            instantiate_items(&items, &TokenStream::new())
+                .unwrap()
+                .to_string(),
+            items.to_string()
+        );
+    }
+
+    #[test]
+    fn debug_dump_filters_by_type_name() {
+        let (config, level) = (
+            Ident::new("Config", Span::call_site()),
+            Ident::new("Level", Span::call_site()),
+        );
+        // No other test reads the variable.
+        std::env::remove_var("RUSTIFACT_DERIVE_DEBUG");
+        assert!(!debug_dump(&config));
+        std::env::set_var("RUSTIFACT_DERIVE_DEBUG", "1");
+        assert!(debug_dump(&config) && debug_dump(&level));
+        std::env::set_var("RUSTIFACT_DERIVE_DEBUG", "Config, Other");
+        assert!(debug_dump(&config) && !debug_dump(&level));
+        std::env::remove_var("RUSTIFACT_DERIVE_DEBUG");
+    }
+}
@@ -0,0 +1,1929 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Emits artifacts of representative derives, which the crate then compiles and checks.
+
+// The generated impls mustn't trip the lints of the crates using the derive.
+#![deny(unused_qualifications, unused_variables)]
+
+use rustifact::internal::{quote, TokenStream};
+use rustifact::ToTokenStream;
+use serde::Serialize;
+
+rustifact_derive::rustifact_encoder_trait!();
+
+// Build-side emission names chrono's items by their paths, which here name stand-ins for them.
+extern crate self as chrono;
+pub use stand_in::Datelike;
+
+#[derive(Serialize, ToTokenStream)]
+#[rustifact(encode = "bincode")]
+pub struct Encoded {
+    pub rows: Vec<(u32, u64)>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Image)]
+#[rustifact(encoder = "Raw")]
+pub struct ImageIn {
+    pub pixels: Vec<u8>,
+}
+
+/// Encodes images as their pixels, which the out type borrows.
+pub struct Raw;
+
+impl RustifactEncoder<ImageIn> for Raw {
+    fn encode(value: &ImageIn) -> Vec<u8> {
+        value.pixels.clone()
+    }
+
+    fn decoder(bytes: TokenStream) -> TokenStream {
+        quote! { Image { pixels: #bytes } }
+    }
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Point)]
+#[rustifact(bake)]
+pub struct PointIn {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Tables)]
+pub struct TablesIn {
+    pub small: [u8; 2],
+    #[rustifact(cfg(feature = "big-tables"))]
+    pub big: [u8; 64],
+}
+
+/// The bitfield storage of bindgen, as generated along with the types using it.
+pub struct __BindgenBitfieldUnit<Storage> {
+    storage: Storage,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(ToTokenStream)]
+#[OutType(Header)]
+#[rustifact(bindgen, strip_prefix = "hdr_")]
+pub struct hdr_t {
+    pub hdr_len: u32,
+    pub _bitfield_align_1: [u8; 0],
+    pub _bitfield_1: __BindgenBitfieldUnit<[u8; 1usize]>,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, ToTokenStream)]
+#[OutType(Word)]
+#[rustifact(union_field = "bytes")]
+pub union word_t {
+    pub bytes: [u8; 2],
+    pub short: u16,
+}
+
+/// The number of characters of `s`.
+pub fn char_count(s: &str) -> usize {
+    s.chars().count()
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Converted)]
+pub struct ConvertedIn {
+    #[rustifact(with = "rustifact::convert::duration")]
+    pub timeout: std::time::Duration,
+    #[rustifact(with = "rustifact::convert::system_time")]
+    pub built: std::time::SystemTime,
+    #[rustifact(with = "rustifact::convert::char_range")]
+    pub letters: std::ops::RangeInclusive<char>,
+    #[rustifact(with = "rustifact::convert::path")]
+    pub dir: std::path::PathBuf,
+    #[rustifact(with = "rustifact::convert::display")]
+    pub addr: std::net::Ipv4Addr,
+    #[rustifact(with = "char_count")]
+    pub name_len: String,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Limits)]
+pub struct LimitsIn {
+    pub max: std::num::NonZeroU32,
+    pub step: Option<std::num::NonZeroU16>,
+    #[rustifact(unchecked)]
+    pub sign: std::num::NonZeroI8,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Spans)]
+pub struct SpansIn {
+    pub bytes: std::ops::Range<usize>,
+    pub digits: std::ops::RangeInclusive<char>,
+    pub nested: std::ops::Range<std::ops::RangeInclusive<u8>>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Endpoints)]
+pub struct EndpointsIn {
+    pub local: std::net::Ipv4Addr,
+    pub any: std::net::IpAddr,
+    pub v6: std::net::SocketAddrV6,
+    pub server: std::net::SocketAddr,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Floats)]
+pub struct FloatsIn {
+    #[rustifact(float_bits)]
+    pub third: f64,
+    #[rustifact(float_bits)]
+    pub nan: f32,
+    #[rustifact(float_bits)]
+    pub scale: Option<f32>,
+    #[rustifact(float_bits)]
+    pub bounds: std::ops::Range<f64>,
+}
+
+/// Writes `items` where `use_symbols!(NAME)` includes them from, for artifacts which aren't
+/// written with `write_static!` or `write_const!`.
+fn write_items(name: &str, items: TokenStream) {
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let path = format!("{}/rustifact_rustifact_derive_tests_{}.rs", out_dir, name);
+    std::fs::write(path, items.to_string()).unwrap();
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Labels)]
+#[rustifact(owned)]
+pub struct LabelsIn {
+    pub title: String,
+    pub alt: Option<&'static str>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Label)]
+pub struct LabelIn {
+    #[rustifact(owned)]
+    pub text: String,
+    pub key: String,
+}
+
+/// An enum of the same name as its out type, which the patterns emitted for it refer to.
+#[derive(ToTokenStream)]
+pub enum Level {
+    Low,
+    High(u8),
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Config)]
+pub struct ConfigIn {
+    pub name: String,
+    #[rustifact(static_ref)]
+    pub levels: Vec<Level>,
+    #[rustifact(static_ref = "Level")]
+    pub default: Level,
+    #[rustifact(static_ref = "u8")]
+    pub path: Vec<u8>,
+}
+
+/// A unit struct, whose impl has nothing to emit from its fields.
+#[derive(ToTokenStream)]
+pub struct Marker;
+
+#[derive(ToTokenStream)]
+#[OutType(Schema)]
+#[rustifact(version = 3)]
+pub struct SchemaIn {
+    pub rows: [u32; 2],
+}
+
+#[derive(ToTokenStream)]
+#[rustifact(fingerprint)]
+pub struct Fingerprinted {
+    pub width: u16,
+}
+
+#[derive(ToTokenStream)]
+#[rustifact(fingerprint)]
+pub struct Retyped {
+    pub width: u32,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Entry)]
+#[rustifact(key = "name")]
+pub struct EntryIn {
+    pub name: String,
+    pub value: u32,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Sample)]
+#[rustifact(stream)]
+pub struct SampleIn {
+    pub id: u16,
+}
+
+#[derive(ToTokenStream)]
+#[rustifact(as_str)]
+pub enum Severity {
+    Debug,
+    #[rustifact(rename = "warning")]
+    Warn,
+    Error(String),
+}
+
+/// An enum of the same name as its out type, in which a variant is renamed.
+#[derive(ToTokenStream)]
+pub enum Mode {
+    Fast,
+    #[rustifact(rename = "Careful")]
+    Slow(u8),
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Codes)]
+pub struct CodesIn {
+    #[rustifact(slice)]
+    pub by_name: std::collections::BTreeMap<String, u16>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Tags)]
+pub struct TagsIn {
+    pub hashed: std::collections::HashSet<String>,
+    pub ordered: std::collections::BTreeSet<u8>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Link)]
+pub struct LinkIn {
+    pub value: u8,
+    pub next: Option<Box<LinkIn>>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Hosts)]
+pub struct HostsIn {
+    pub addrs: [std::net::Ipv4Addr; 2],
+    pub steps: [Option<std::num::NonZeroU8>; 2],
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Timed)]
+pub struct TimedIn {
+    #[rustifact(with(1 = "rustifact::convert::duration"))]
+    pub entry: (std::net::Ipv4Addr, std::time::Duration, u8),
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Squares)]
+pub struct SquaresIn {
+    #[rustifact(iter)]
+    pub values: std::iter::Map<std::ops::Range<u32>, fn(u32) -> u32>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Flags)]
+pub struct FlagsIn {
+    #[rustifact(runs)]
+    pub set: Vec<bool>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Unit)]
+#[rustifact(group = "UNITS")]
+pub struct UnitIn {
+    pub symbol: char,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Palette)]
+#[rustifact(writer, version = 2)]
+pub struct PaletteIn {
+    pub colours: [u32; 2],
+}
+
+#[derive(ToTokenStream)]
+#[rustifact(with = "celsius")]
+pub struct Celsius(f64);
+
+mod celsius {
+    use super::*;
+
+    pub fn to_toks(value: &Celsius, toks: &mut TokenStream) {
+        let kelvin = value.0 + 273.15;
+        toks.extend(quote! { Kelvin(#kelvin) });
+    }
+}
+
+pub trait Shape {
+    fn sides(&self) -> u8;
+}
+
+pub struct Square;
+
+impl Shape for Square {
+    fn sides(&self) -> u8 {
+        4
+    }
+}
+
+fn shape_toks(shape: &dyn Shape) -> TokenStream {
+    let sides = shape.sides();
+    quote! { Polygon { sides: #sides } }
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Drawing)]
+pub struct DrawingIn {
+    #[rustifact(dyn_with = "shape_toks")]
+    pub main: Box<dyn Shape>,
+    #[rustifact(dyn_with = "shape_toks")]
+    pub extra: Option<Box<dyn Shape>>,
+}
+
+/// Defines a struct with a field of type `$t`, as substituted types are wrapped in invisible
+/// groups.
+macro_rules! with_field_of {
+    ($name:ident, $out:ident, $t:ty) => {
+        #[derive(ToTokenStream)]
+        #[OutType($out)]
+        pub struct $name {
+            pub value: $t,
+        }
+    };
+}
+
+with_field_of!(MacroedIn, Macroed, Option<std::num::NonZeroU64>);
+
+#[derive(ToTokenStream)]
+#[OutType(SelfLink)]
+pub struct SelfLinkIn {
+    pub value: u8,
+    pub next: Option<Box<Self>>,
+}
+
+/// The length of a label of any string type, so the label needs no `ToTokenStream` bound.
+pub fn label_len<L: AsRef<str>>(label: &L) -> usize {
+    label.as_ref().len()
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Tagged)]
+pub struct TaggedIn<T, L: AsRef<str>> {
+    pub value: T,
+    #[rustifact(with = "label_len")]
+    pub label: L,
+}
+
+/// A label type with no `ToTokenStream` impl.
+pub struct Label(&'static str);
+
+impl AsRef<str> for Label {
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
+pub const WIDTH: usize = 2;
+
+pub const fn edge_len(width: usize) -> usize {
+    width + 1
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Grid)]
+pub struct GridIn {
+    pub cells: [u8; WIDTH * 2],
+    pub edge: [u8; edge_len(WIDTH)],
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Table)]
+#[rustifact(writer)]
+pub struct TableIn {
+    /// The names of the rows.
+    #[rustifact(hoist)]
+    pub names: Vec<String>,
+    #[rustifact(hoist)]
+    pub weights: [Option<u16>; 3],
+    #[rustifact(hoist = "Level")]
+    pub level: Level,
+}
+
+#[derive(ToTokenStream)]
+#[non_exhaustive]
+#[rustifact(fallback = "Kind::Unknown")]
+pub enum Kind {
+    Known(u8),
+    Unknown,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Row)]
+#[rustifact(bulk)]
+pub struct RowIn {
+    pub id: u32,
+    pub weight: f32,
+}
+
+#[derive(PartialEq, ToTokenStream)]
+#[OutType(Glyph)]
+#[rustifact(base = "DEFAULT_GLYPH")]
+pub struct GlyphIn {
+    pub fields: u32,
+    pub code: u32,
+    pub printable: bool,
+}
+
+pub const DEFAULT_GLYPH: GlyphIn = GlyphIn {
+    fields: 0,
+    code: 0,
+    printable: true,
+};
+
+#[derive(ToTokenStream)]
+#[OutType(Costs)]
+pub struct CostsIn {
+    #[rustifact(sparse)]
+    pub by_op: std::collections::BTreeMap<u8, u16>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Digest)]
+#[rustifact(writer, checksum)]
+pub struct DigestIn {
+    pub bytes: [u8; 3],
+}
+
+#[allow(non_camel_case_types)]
+#[derive(ToTokenStream)]
+#[OutType(Opcode)]
+#[rustifact(rename_all_variants = "PascalCase")]
+pub enum RawOpcode {
+    LOAD_CONST,
+    JUMP_IF(u32),
+    #[rustifact(rename = "Halt")]
+    STOP_NOW,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Chunked)]
+pub struct ChunkedIn {
+    #[rustifact(chunk = 2)]
+    pub values: Vec<u16>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Widened)]
+#[rustifact(unsuffixed)]
+pub struct WidenedIn {
+    pub count: u8,
+    pub ratio: f32,
+    pub steps: [Option<u16>; 2],
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Samples)]
+pub struct SamplesIn {
+    #[rustifact(sidecar)]
+    pub values: Vec<u32>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Prefixed)]
+#[rustifact(
+    path_prefix = "crate::prefixed",
+    path_prefix_env = "RUSTIFACT_TESTS_UNSET_PREFIX"
+)]
+pub struct PrefixedIn {
+    pub id: u8,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(View<'static>)]
+pub struct ViewIn {
+    pub name: String,
+}
+
+/// Generic over the same names as the generated methods, which mustn't clash with them.
+#[derive(ToTokenStream)]
+#[OutType(Keyed)]
+pub struct KeyedIn<'a, 'b: 'a, K, V>
+where
+    V: Default + PartialEq + 'a,
+{
+    pub name: &'b str,
+    pub first: &'a K,
+    #[rustifact(sparse)]
+    pub by: std::collections::BTreeMap<K, V>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Closed)]
+pub struct ClosedIn {
+    #[rustifact(with = |d: &std::time::Duration| d.as_secs() as u32)]
+    pub idle_secs: std::time::Duration,
+    #[rustifact(with = "|s: &String| s.len()")]
+    pub name_len: String,
+}
+
+/// A container from elsewhere, iterable by reference only.
+pub struct Ring(Vec<u8>);
+
+impl<'a> IntoIterator for &'a Ring {
+    type Item = &'a u8;
+    type IntoIter = std::slice::Iter<'a, u8>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Rings)]
+pub struct RingsIn {
+    #[rustifact(collection)]
+    pub plain: Ring,
+    #[rustifact(collection, with = |b: &u8| u16::from(*b) * 100)]
+    pub scaled: Ring,
+}
+
+/// Stand-ins for the types of crates recognised by name, which the build can't depend on.
+mod stand_in {
+    /// Keeps insertion order, as indexmap's types do.
+    pub struct IndexMap<K, V>(pub Vec<(K, V)>);
+
+    impl<K, V> IndexMap<K, V> {
+        pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+            self.0.iter().map(|(k, v)| (k, v))
+        }
+    }
+
+    pub struct IndexSet<T>(pub Vec<T>);
+
+    impl<T> IndexSet<T> {
+        pub fn iter(&self) -> std::slice::Iter<'_, T> {
+            self.0.iter()
+        }
+    }
+
+    /// Holds its inline capacity in full.
+    pub struct SmallVec<A>(pub A);
+
+    impl<T, const N: usize> SmallVec<[T; N]> {
+        pub fn iter(&self) -> std::slice::Iter<'_, T> {
+            self.0.iter()
+        }
+    }
+
+    pub struct ArrayVec<T, const N: usize>(pub Vec<T>);
+
+    impl<T, const N: usize> ArrayVec<T, N> {
+        pub fn iter(&self) -> std::slice::Iter<'_, T> {
+            self.0.iter()
+        }
+    }
+
+    pub struct SmolStr(pub &'static str);
+
+    impl AsRef<str> for SmolStr {
+        fn as_ref(&self) -> &str {
+            self.0
+        }
+    }
+
+    pub struct CompactString(pub String);
+
+    impl AsRef<str> for CompactString {
+        fn as_ref(&self) -> &str {
+            &self.0
+        }
+    }
+
+    pub struct Uuid(pub u128);
+
+    impl Uuid {
+        pub fn as_u128(&self) -> u128 {
+            self.0
+        }
+    }
+
+    pub trait Datelike {
+        fn year(&self) -> i32;
+        fn month(&self) -> u32;
+        fn day(&self) -> u32;
+    }
+
+    pub struct NaiveDate(pub i32, pub u32, pub u32);
+
+    impl Datelike for NaiveDate {
+        fn year(&self) -> i32 {
+            self.0
+        }
+
+        fn month(&self) -> u32 {
+            self.1
+        }
+
+        fn day(&self) -> u32 {
+            self.2
+        }
+    }
+
+    pub struct Utc;
+
+    pub struct DateTime<Tz>(pub i64, pub u32, pub Tz);
+
+    impl DateTime<Utc> {
+        pub fn timestamp(&self) -> i64 {
+            self.0
+        }
+
+        pub fn timestamp_subsec_nanos(&self) -> u32 {
+            self.1
+        }
+    }
+
+    pub struct UtcOffset(pub i32);
+
+    impl UtcOffset {
+        pub fn whole_seconds(&self) -> i32 {
+            self.0
+        }
+    }
+
+    pub struct OffsetDateTime(pub i128, pub UtcOffset);
+
+    impl OffsetDateTime {
+        pub fn unix_timestamp_nanos(&self) -> i128 {
+            self.0
+        }
+
+        pub fn offset(&self) -> &UtcOffset {
+            &self.1
+        }
+    }
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Ordered)]
+pub struct OrderedIn {
+    pub by_name: stand_in::IndexMap<&'static str, u8>,
+    #[rustifact(sorted)]
+    pub sorted: stand_in::IndexMap<&'static str, u8>,
+    pub ids: stand_in::IndexSet<u16>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Inline)]
+pub struct InlineIn {
+    pub small: stand_in::SmallVec<[u8; 2]>,
+    #[rustifact(array)]
+    pub fixed: stand_in::ArrayVec<u16, 4>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Strings)]
+pub struct StringsIn {
+    pub smol: stand_in::SmolStr,
+    pub compact: Option<stand_in::CompactString>,
+    #[rustifact(string_like)]
+    pub label: Label,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Device)]
+pub struct DeviceIn {
+    pub id: stand_in::Uuid,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Release)]
+pub struct ReleaseIn {
+    pub date: stand_in::NaiveDate,
+    pub built: stand_in::DateTime<stand_in::Utc>,
+    pub signed: stand_in::OffsetDateTime,
+}
+
+/// Stands in for a type generated by `bitflags!`.
+pub struct Perms(u8);
+
+impl Perms {
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+}
+
+#[derive(ToTokenStream)]
+#[OutType(File)]
+pub struct FileIn {
+    pub name: &'static str,
+    #[rustifact(bitflags)]
+    pub perms: Perms,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Masks)]
+pub struct MasksIn {
+    #[rustifact(radix = "hex")]
+    pub magic: u32,
+    #[rustifact(radix = "bin")]
+    pub low: i8,
+    #[rustifact(radix = "oct")]
+    pub mode: Option<u16>,
+    #[rustifact(radix = "hex", unsuffixed)]
+    pub lanes: [u64; 2],
+}
+
+#[derive(PartialEq, ToTokenStream)]
+#[OutType(Style)]
+#[rustifact(bitpack = "flags", base = "DEFAULT_STYLE")]
+pub struct StyleIn {
+    pub size: u8,
+    pub spacing: u8,
+    pub bold: bool,
+    pub italic: bool,
+}
+
+pub const DEFAULT_STYLE: StyleIn = StyleIn {
+    size: 12,
+    spacing: 1,
+    bold: false,
+    italic: false,
+};
+
+#[derive(ToTokenStream)]
+#[OutType(Rule)]
+pub struct RuleIn {
+    pub name: &'static str,
+    #[rustifact(runtime_init = "crate::compile_pattern")]
+    pub pattern: &'static str,
+}
+
+/// How many values `percentage` has checked.
+static CHECKED: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+fn percentage(p: &u8) -> Result<(), String> {
+    CHECKED.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    if *p <= 100 {
+        Ok(())
+    } else {
+        Err(format!("{} is over 100", p))
+    }
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Discount)]
+pub struct DiscountIn {
+    pub code: &'static str,
+    #[rustifact(validate = "percentage")]
+    pub percent: u8,
+}
+
+fn by_descending(a: &u16, b: &u16) -> std::cmp::Ordering {
+    b.cmp(a)
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Sorted)]
+pub struct SortedIn {
+    #[rustifact(sort_by = "item")]
+    pub ids: [u32; 3],
+    #[rustifact(sort_by = "item.1")]
+    pub ranked: [(&'static str, u8); 3],
+    #[rustifact(sort_by = "by_descending")]
+    pub ports: [u16; 3],
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Merged)]
+pub struct MergedIn {
+    #[rustifact(hoist, dedup)]
+    pub names: Vec<&'static str>,
+    #[rustifact(hoist, sort_by = "item", dedup)]
+    pub codes: Vec<u8>,
+}
+
+#[derive(Debug, ToTokenStream)]
+#[OutType(Setting)]
+pub struct SettingIn {
+    pub id: u8,
+    pub value: u16,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Settings)]
+pub struct SettingsIn {
+    #[rustifact(unique_key = "id")]
+    pub settings: [SettingIn; 2],
+}
+
+#[derive(ToTokenStream)]
+#[OutFields(sizes: &'static [u32], scale: Option<f32>, level: &'static Level)]
+#[OutType(Font)]
+pub struct FontIn {
+    pub sizes: Vec<u32>,
+    pub scale: Option<f32>,
+    #[rustifact(hoist)]
+    pub level: Level,
+}
+
+#[derive(ToTokenStream)]
+#[rustifact(arena = "NodeId")]
+#[OutType(Node)]
+pub struct NodeIn {
+    pub name: &'static str,
+    pub children: Vec<NodeIn>,
+    pub next: Option<Box<NodeIn>>,
+}
+
+impl NodeIn {
+    fn leaf(name: &'static str) -> Self {
+        NodeIn {
+            name,
+            children: Vec::new(),
+            next: None,
+        }
+    }
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Lang)]
+pub enum LangIn {
+    Rust,
+    #[rustifact(as_other)]
+    Experimental(String),
+    #[rustifact(as_other)]
+    Zig,
+    #[rustifact(other, rename = "Unlisted")]
+    Other,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Probe)]
+pub struct ProbeIn {
+    pub host: &'static str,
+    pub outcome: Result<u16, String>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Routes)]
+pub struct RoutesIn {
+    #[rustifact(multimap)]
+    pub by_host: std::collections::HashMap<&'static str, Vec<u16>>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Queues)]
+pub struct QueuesIn {
+    pub pending: std::collections::VecDeque<u8>,
+    #[rustifact(sorted)]
+    pub history: std::collections::LinkedList<u8>,
+    pub urgent: std::collections::BinaryHeap<u8>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Quota)]
+pub struct QuotaIn {
+    #[rustifact(unwrap_or_default)]
+    pub retries: Option<u8>,
+    #[rustifact(unwrap_or_default)]
+    pub timeout: Result<u32, String>,
+    #[rustifact(expect = "the quota needs a name")]
+    pub name: Option<&'static str>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Block)]
+pub struct BlockIn {
+    #[rustifact(pad_to = 4)]
+    pub lanes: Vec<u16>,
+    #[rustifact(pad_to = 3, pad_with = "\"-\"")]
+    pub labels: Vec<&'static str>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Roster)]
+pub struct RosterIn {
+    #[rustifact(min_len = 1, max_len = 3)]
+    pub members: [&'static str; 2],
+    #[rustifact(max_len = 4, hoist)]
+    pub scores: Vec<u8>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Rgb)]
+pub struct RgbIn {
+    #[rustifact(array_len = 3)]
+    pub channels: Vec<u8>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Pair)]
+#[rustifact(impl_for(PairIn<u8>, PairIn<u16>), key = "k")]
+pub struct PairIn<T> {
+    pub k: T,
+    pub v: u32,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Shared)]
+#[rustifact(impl_cfg = "all()")]
+pub struct SharedIn {
+    pub id: u8,
+}
+
+/// Not `ToTokenStream`, which the impls gated off below would need.
+pub struct Opaque;
+
+#[derive(ToTokenStream)]
+#[rustifact(impl_cfg = "any()")]
+pub struct GatedOff {
+    pub opaque: Opaque,
+}
+
+/// The context of emission of layouts, scaling their lengths.
+#[derive(Default)]
+pub struct Units {
+    pub scale: u32,
+}
+
+/// Emits `len` in the units of `ctx`.
+pub fn scaled(len: &u32, ctx: &Units) -> TokenStream {
+    (len * ctx.scale.max(1)).to_tok_stream()
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Extent)]
+#[rustifact(context = "Units")]
+pub struct ExtentIn {
+    #[rustifact(with_context = "scaled")]
+    pub len: u32,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Layout)]
+#[rustifact(context = "Units")]
+pub struct LayoutIn {
+    pub name: String,
+    #[rustifact(context)]
+    pub extent: ExtentIn,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Tree)]
+#[rustifact(msrv = "1.70")]
+pub struct TreeIn {
+    pub label: char,
+    #[rustifact(static_ref = "Level")]
+    pub level: Level,
+    #[rustifact(static_ref = "u8")]
+    pub path: Vec<u8>,
+    #[rustifact(hoist)]
+    pub weights: [u16; 2],
+    pub id: std::num::NonZeroU32,
+    #[rustifact(float_bits)]
+    pub ratio: f32,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Budget)]
+#[rustifact(max_tokens = 24)]
+pub struct BudgetIn {
+    pub costs: [u32; 4],
+    pub extra: Option<[u32; 16]>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Gauge)]
+#[rustifact(metrics, writer)]
+pub struct GaugeIn {
+    pub low: u16,
+    pub high: u16,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Suit)]
+#[rustifact(all_variants)]
+pub enum SuitIn {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Shuffled)]
+pub struct ShuffledIn {
+    #[rustifact(collection, sorted)]
+    pub ring: Ring,
+    #[rustifact(collection)]
+    pub unsorted: Ring,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Interned)]
+pub struct InternedIn {
+    pub boxed: Box<str>,
+    pub shared: std::rc::Rc<str>,
+    pub atomic: std::sync::Arc<str>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(InternedOwned)]
+#[rustifact(owned)]
+pub struct InternedOwnedIn {
+    pub boxed: Box<str>,
+    pub atomic: std::sync::Arc<str>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Borrowed)]
+pub struct BorrowedIn {
+    pub text: std::borrow::Cow<'static, str>,
+    pub steps: std::borrow::Cow<'static, [u16]>,
+    pub limit: std::borrow::Cow<'static, u32>,
+    #[rustifact(cow)]
+    pub label: std::borrow::Cow<'static, str>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Linked)]
+pub struct LinkedIn {
+    pub id: u8,
+    pub parent: std::rc::Weak<u8>,
+    pub shared: std::sync::Weak<u8>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Offsets)]
+#[rustifact(usize_as = "u32")]
+pub struct OffsetsIn {
+    pub start: usize,
+    pub ends: [usize; 2],
+    pub last: Option<usize>,
+    #[rustifact(usize_as = "i16")]
+    pub delta: isize,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Counters)]
+pub struct CountersIn {
+    pub hits: std::sync::atomic::AtomicU32,
+    pub ready: std::sync::atomic::AtomicBool,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Locked)]
+pub struct LockedIn {
+    pub queue: std::sync::Mutex<[u8; 2]>,
+    pub name: std::sync::RwLock<&'static str>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Celled)]
+pub struct CelledIn {
+    pub count: std::cell::Cell<u16>,
+    pub level: std::cell::RefCell<Level>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Cached)]
+pub struct CachedIn {
+    pub words: [&'static str; 3],
+    pub longest: std::sync::OnceLock<usize>,
+    #[rustifact(seed)]
+    pub total: std::sync::OnceLock<usize>,
+    #[rustifact(seed)]
+    pub first: std::cell::OnceCell<&'static str>,
+}
+
+fn double(n: u32) -> u32 {
+    n * 2
+}
+
+fn triple(n: u32) -> u32 {
+    n * 3
+}
+
+/// The path in the artifact's crate of the functions of `HandlersIn`, told apart by their output.
+fn handler_path(f: &fn(u32) -> u32) -> &'static str {
+    match f(1) {
+        2 => "crate::double",
+        _ => "crate::triple",
+    }
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Handlers)]
+pub struct HandlersIn {
+    #[rustifact(fn_path = "crate::double")]
+    pub on_x: fn(u32) -> u32,
+    #[rustifact(fn_path_of = "handler_path")]
+    pub table: [fn(u32) -> u32; 2],
+}
+
+#[derive(ToTokenStream)]
+#[OutFields(name: &'static str, steps: &'static [u16], weight: u8)]
+#[OutType(Stage)]
+#[rustifact(chain)]
+pub struct StageIn {
+    pub name: String,
+    pub steps: Vec<u16>,
+    pub weight: u8,
+}
+
+/// The out type of `StageIn`, as included by a later stage of the build.
+pub struct Stage {
+    pub name: &'static str,
+    pub steps: &'static [u16],
+    pub weight: u8,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Figure)]
+pub enum FigureIn {
+    Circle {
+        radius: u16,
+    },
+    Rect {
+        w: u16,
+        #[rustifact(with = |h: &u8| u16::from(*h))]
+        h: u8,
+    },
+    Dot,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Commands)]
+pub struct CommandsIn {
+    #[rustifact(trie)]
+    pub by_name: std::collections::HashMap<String, u8>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Currencies)]
+pub struct CurrenciesIn {
+    #[rustifact(lookup = "binary_search")]
+    pub by_code: std::collections::HashMap<String, u16>,
+    #[rustifact(lookup = "binary_search")]
+    pub by_digit: std::collections::BTreeMap<u8, char>,
+}
+
+#[derive(ToTokenStream)]
+#[OutType(Lexer)]
+pub struct LexerIn {
+    #[rustifact(state_machine)]
+    pub table: Vec<Vec<u8>>,
+    #[rustifact(state_machine)]
+    pub named: std::collections::BTreeMap<(char, String), u16>,
+}
+
+#[derive(Serialize, ToTokenStream)]
+#[rustifact(encode = "bincode", compress = "zstd")]
+pub struct Packed {
+    pub samples: Vec<u16>,
+}
+
+fn main() {
+    let encoded = Encoded {
+        rows: vec![(1, 10), (2, 20)],
+    };
+    rustifact::write_static!(ENCODED, std::sync::LazyLock<Encoded>, &encoded);
+
+    let image = ImageIn {
+        pixels: vec![0, 127, 255],
+    };
+    rustifact::write_static!(IMAGE, std::sync::LazyLock<Image>, &image);
+
+    let point = PointIn { x: 1, y: -2 };
+    let baked = databake::Bake::bake(&point, &databake::CrateEnv::default());
+    write_items("BAKED", quote! { static BAKED: Point = #baked; });
+
+    let tables = TablesIn {
+        small: [1, 2],
+        big: [3; 64],
+    };
+    rustifact::write_static!(TABLES, Tables, &tables);
+
+    let header = hdr_t {
+        hdr_len: 12,
+        _bitfield_align_1: [],
+        _bitfield_1: __BindgenBitfieldUnit { storage: [0b101] },
+    };
+    rustifact::write_static!(HEADER, Header, &header);
+    rustifact::write_static!(WORD, Word, &word_t { bytes: [1, 2] });
+
+    let converted = ConvertedIn {
+        timeout: std::time::Duration::new(3, 500),
+        built: std::time::UNIX_EPOCH + std::time::Duration::new(60, 7),
+        letters: 'a'..='f',
+        dir: std::path::PathBuf::from("data/tables"),
+        addr: std::net::Ipv4Addr::new(10, 0, 0, 1),
+        name_len: "héllo".to_string(),
+    };
+    rustifact::write_static!(CONVERTED, Converted, &converted);
+
+    let limits = LimitsIn {
+        max: std::num::NonZeroU32::new(100).unwrap(),
+        step: std::num::NonZeroU16::new(5),
+        sign: std::num::NonZeroI8::new(-1).unwrap(),
+    };
+    rustifact::write_static!(LIMITS, Limits, &limits);
+
+    let spans = SpansIn {
+        bytes: 2..8,
+        digits: '0'..='9',
+        nested: (1..=2)..(3..=4),
+    };
+    rustifact::write_static!(SPANS, Spans, &spans);
+
+    let endpoints = EndpointsIn {
+        local: std::net::Ipv4Addr::LOCALHOST,
+        any: "2001:db8::1".parse().unwrap(),
+        v6: std::net::SocketAddrV6::new(std::net::Ipv6Addr::LOCALHOST, 443, 1, 2),
+        server: "192.168.0.1:8080".parse().unwrap(),
+    };
+    rustifact::write_static!(ENDPOINTS, Endpoints, &endpoints);
+
+    let floats = FloatsIn {
+        third: 1.0 / 3.0,
+        nan: f32::from_bits(0x7fc0_0123),
+        scale: Some(0.1),
+        bounds: -0.0..f64::MAX,
+    };
+    rustifact::write_static!(FLOATS, Floats, &floats);
+
+    let labels = LabelsIn {
+        title: "Title".to_string(),
+        alt: Some("alt"),
+    }
+    .to_tok_stream();
+    let label = LabelIn {
+        text: "text".to_string(),
+        key: "k".to_string(),
+    }
+    .to_tok_stream();
+    write_items(
+        "OWNED",
+        quote! {
+            fn labels() -> Labels { #labels }
+            fn label() -> Label { #label }
+        },
+    );
+
+    let config = ConfigIn {
+        name: "main".to_string(),
+        levels: vec![Level::Low, Level::High(3)],
+        default: Level::High(1),
+        path: vec![0, 2],
+    };
+    rustifact::write_static!(CONFIG, Config, &config);
+    rustifact::write_const!(CONFIG_CONST, Config, &config);
+
+    let schema = SchemaIn { rows: [4, 5] };
+    assert_eq!(SchemaIn::RUSTIFACT_VERSION, 3);
+    rustifact::write_static!(SCHEMA, Schema, &schema);
+    write_items("SCHEMA_VERSION", SchemaIn::version_items("SCHEMA"));
+
+    assert_ne!(
+        Fingerprinted::RUSTIFACT_FINGERPRINT,
+        Retyped::RUSTIFACT_FINGERPRINT
+    );
+
+    let entries = [("one", 1), ("two", 2), ("three", 3)].map(|(name, value)| EntryIn {
+        name: name.to_string(),
+        value,
+    });
+    assert_eq!(entries[0].key_tokens().to_string(), "\"one\"");
+    let mut map = rustifact::MapBuilder::new();
+    for (k, v) in entries.iter().map(EntryIn::keyed) {
+        map.entry(k, v);
+    }
+    rustifact::write_static!(ENTRIES, rustifact::Map<&'static str, Entry>, &map);
+
+    let samples: Vec<SampleIn> = (0..100).map(|id| SampleIn { id }).collect();
+    assert_eq!(SampleIn::emit_elements(&samples).count(), 100);
+    let mut source = b"static SAMPLES: [Sample; 100] = ".to_vec();
+    SampleIn::write_elements(&samples, &mut source).unwrap();
+    source.push(b';');
+    write_items(
+        "SAMPLES",
+        String::from_utf8(source).unwrap().parse().unwrap(),
+    );
+
+    let severities = [
+        Severity::Debug,
+        Severity::Warn,
+        Severity::Error("disk".to_string()),
+    ];
+    rustifact::write_static!(SEVERITIES, [&str; 3], &severities);
+    rustifact::write_static!(MODES, [Mode; 2], &[Mode::Fast, Mode::Slow(2)]);
+
+    let codes = CodesIn {
+        by_name: [("ok", 200), ("gone", 410), ("moved", 301)]
+            .iter()
+            .map(|(k, v)| (k.to_string(), *v))
+            .collect(),
+    };
+    rustifact::write_static!(CODES, Codes, &codes);
+
+    let tags = TagsIn {
+        hashed: ["b", "a", "c"].iter().map(|s| s.to_string()).collect(),
+        ordered: [3, 1, 2].into(),
+    };
+    rustifact::write_static!(TAGS, Tags, &tags);
+
+    let chain = LinkIn {
+        value: 1,
+        next: Some(Box::new(LinkIn {
+            value: 2,
+            next: None,
+        })),
+    };
+    rustifact::write_static!(CHAIN, Link, &chain);
+
+    let hosts = HostsIn {
+        addrs: [std::net::Ipv4Addr::LOCALHOST, std::net::Ipv4Addr::BROADCAST],
+        steps: [std::num::NonZeroU8::new(4), None],
+    };
+    rustifact::write_static!(HOSTS, Hosts, &hosts);
+
+    let timed = TimedIn {
+        entry: (
+            std::net::Ipv4Addr::new(1, 2, 3, 4),
+            std::time::Duration::from_millis(1500),
+            9,
+        ),
+    };
+    rustifact::write_static!(TIMED, Timed, &timed);
+
+    let squares = SquaresIn {
+        values: (1..5).map(|n| n * n),
+    };
+    rustifact::write_static!(SQUARES, Squares, &squares);
+
+    let flags = FlagsIn {
+        set: (0..20).map(|i| (3..7).contains(&i) || i >= 18).collect(),
+    };
+    assert!(FlagsIn::rustifact_runs_contain(&[(3, 7)], 6));
+    rustifact::write_static!(FLAGS, Flags, &flags);
+
+    let units = [UnitIn { symbol: 'm' }, UnitIn { symbol: 's' }];
+    let units = units.iter().map(ToTokenStream::to_tok_stream);
+    write_items(
+        UnitIn::RUSTIFACT_GROUP,
+        quote! { static UNITS: [Unit; 2] = [#(#units),*]; },
+    );
+
+    PaletteIn {
+        colours: [0xff0000, 0x00ff00],
+    }
+    .write_artifact("PALETTE");
+
+    rustifact::write_static!(BOILING, Kelvin, &Celsius(100.0));
+
+    let drawing = DrawingIn {
+        main: Box::new(Square),
+        extra: None,
+    };
+    rustifact::write_static!(DRAWING, Drawing, &drawing);
+
+    let macroed = MacroedIn {
+        value: std::num::NonZeroU64::new(42),
+    };
+    rustifact::write_static!(MACROED, Macroed, &macroed);
+
+    let self_chain = SelfLinkIn {
+        value: 3,
+        next: Some(Box::new(SelfLinkIn {
+            value: 4,
+            next: None,
+        })),
+    };
+    rustifact::write_static!(SELF_CHAIN, SelfLink, &self_chain);
+
+    let tagged = TaggedIn {
+        value: 7u16,
+        label: Label("seven"),
+    };
+    rustifact::write_static!(TAGGED, Tagged, &tagged);
+
+    let grid = GridIn {
+        cells: [1, 2, 3, 4],
+        edge: [5, 6, 7],
+    };
+    rustifact::write_static!(GRID, Grid, &grid);
+
+    let table = TableIn {
+        names: vec!["x".to_string(), "y".to_string()],
+        weights: [Some(1), None, Some(3)],
+        level: Level::High(9),
+    };
+    table.write_artifact("TABLE");
+    rustifact::write_static!(TABLE_INLINE, Table, &table);
+
+    rustifact::write_static!(KINDS, [Kind; 2], &[Kind::Known(1), Kind::Unknown]);
+
+    let rows: Vec<RowIn> = (0..4)
+        .map(|id| RowIn {
+            id,
+            weight: id as f32 / 2.0,
+        })
+        .collect();
+    let rows = RowIn::emit_slice(&rows);
+    write_items("ROWS", quote! { static ROWS: &[Row] = #rows; });
+
+    let glyphs = [
+        GlyphIn {
+            fields: 2,
+            code: 0,
+            printable: false,
+        },
+        GlyphIn {
+            code: 65,
+            ..DEFAULT_GLYPH
+        },
+    ];
+    rustifact::write_static!(GLYPHS, [Glyph; 2], &glyphs);
+
+    let costs = CostsIn {
+        by_op: [(1, 0), (2, 5), (3, 0), (4, 9)].into_iter().collect(),
+    };
+    let default = 0;
+    assert_eq!(
+        CostsIn::rustifact_sparse_get(&[(2u8, 5u16), (4, 9)], &3, &default),
+        &0
+    );
+    rustifact::write_static!(COSTS, Costs, &costs);
+
+    let digest = DigestIn { bytes: [1, 2, 3] };
+    assert_eq!(digest.checksum(), digest.checksum());
+    assert_ne!(digest.checksum(), DigestIn { bytes: [1, 2, 4] }.checksum());
+    digest.write_artifact("DIGEST");
+    let sum = digest.checksum();
+    write_items("DIGEST_SUM", quote! { pub const DIGEST_SUM: u64 = #sum; });
+
+    let ops = [
+        RawOpcode::LOAD_CONST,
+        RawOpcode::JUMP_IF(4),
+        RawOpcode::STOP_NOW,
+    ];
+    rustifact::write_static!(OPCODES, [Opcode; 3], &ops);
+
+    // The impls of a type local to a function are wrapped within it.
+    #[derive(ToTokenStream)]
+    #[OutType(Local)]
+    struct LocalIn {
+        x: u8,
+    }
+    rustifact::write_static!(LOCAL, Local, &LocalIn { x: 11 });
+
+    let chunked = ChunkedIn {
+        values: vec![1, 2, 3, 4, 5],
+    };
+    rustifact::write_static!(CHUNKED, Chunked, &chunked);
+
+    let widened = WidenedIn {
+        count: 200,
+        ratio: 0.5,
+        steps: [Some(3), None],
+    };
+    rustifact::write_static!(WIDENED, Widened, &widened);
+
+    let samples = SamplesIn {
+        values: vec![1, 70_000, 3],
+    };
+    rustifact::write_static!(SIDECAR, Samples, &samples);
+    let bytes = [0, 0, 0, 0, 2, 1, 0, 0];
+    assert_eq!(SamplesIn::rustifact_sidecar_get_u32(&bytes, 1), Some(258));
+    assert_eq!(SamplesIn::rustifact_sidecar_get_u32(&bytes, 2), None);
+
+    rustifact::write_static!(PREFIXED, prefixed::Prefixed, &PrefixedIn { id: 5 });
+
+    write_items("TABLE_CONST", table.emit_const("TABLE_CONST", "pub"));
+    let palette = PaletteIn {
+        colours: [0x0000ff, 0xffffff],
+    };
+    write_items("SHADES", palette.emit_static("SHADES", "pub(crate)"));
+
+    let view = ViewIn {
+        name: "borrowed".to_string(),
+    };
+    rustifact::write_static!(VIEW, View<'static>, &view);
+
+    let first = 1u8;
+    let keyed = KeyedIn {
+        name: "keyed",
+        first: &first,
+        by: [(1u8, 0u16), (2, 6)].into_iter().collect(),
+    };
+    assert_eq!(
+        KeyedIn::<u8, u16>::rustifact_sparse_get(&[(2u8, 6u16)], &2, &0),
+        &6
+    );
+    rustifact::write_static!(KEYED, Keyed, &keyed);
+
+    let closed = ClosedIn {
+        idle_secs: std::time::Duration::from_millis(90_500),
+        name_len: "four".to_string(),
+    };
+    rustifact::write_static!(CLOSED, Closed, &closed);
+
+    let rings = RingsIn {
+        plain: Ring(vec![3, 1, 2]),
+        scaled: Ring(vec![1, 2]),
+    };
+    rustifact::write_static!(RINGS, Rings, &rings);
+
+    let ordered = OrderedIn {
+        by_name: stand_in::IndexMap(vec![("b", 1), ("a", 2)]),
+        sorted: stand_in::IndexMap(vec![("b", 1), ("a", 2)]),
+        ids: stand_in::IndexSet(vec![9, 4]),
+    };
+    rustifact::write_static!(ORDERED, Ordered, &ordered);
+
+    let inline = InlineIn {
+        small: stand_in::SmallVec([1, 2]),
+        fixed: stand_in::ArrayVec(vec![5, 6, 7]),
+    };
+    rustifact::write_static!(INLINE, Inline, &inline);
+
+    let strings = StringsIn {
+        smol: stand_in::SmolStr("smol"),
+        compact: Some(stand_in::CompactString("compact".to_string())),
+        label: Label("own"),
+    };
+    rustifact::write_static!(STRINGS, Strings, &strings);
+
+    let device = DeviceIn {
+        id: stand_in::Uuid(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8),
+    };
+    rustifact::write_const!(DEVICE, Device, &device);
+
+    let release = ReleaseIn {
+        date: stand_in::NaiveDate(2024, 2, 29),
+        built: stand_in::DateTime(1_700_000_000, 5, stand_in::Utc),
+        signed: stand_in::OffsetDateTime(42, stand_in::UtcOffset(3600)),
+    };
+    rustifact::write_const!(RELEASE, Release, &release);
+
+    let file = FileIn {
+        name: "run.sh",
+        perms: Perms(0b1000_0101),
+    };
+    rustifact::write_const!(FILE, File, &file);
+
+    let masks = MasksIn {
+        magic: 0xDEAD_BEEF,
+        low: -0b101,
+        mode: Some(0o755),
+        lanes: [0xFF, 0x1_0000],
+    };
+    rustifact::write_const!(MASKS, Masks, &masks);
+
+    let styles = [
+        StyleIn {
+            bold: true,
+            italic: true,
+            ..DEFAULT_STYLE
+        },
+        StyleIn {
+            size: 16,
+            ..DEFAULT_STYLE
+        },
+    ];
+    assert_eq!(StyleIn::ITALIC_BIT, 0b10);
+    rustifact::write_static!(STYLES, [Style; 2], &styles);
+
+    let rule = RuleIn {
+        name: "digits",
+        pattern: "0-9",
+    };
+    rustifact::write_static!(RULE, Rule, &rule);
+
+    let discounts = [
+        DiscountIn {
+            code: "HALF",
+            percent: 50,
+        },
+        DiscountIn {
+            code: "FREE",
+            percent: 100,
+        },
+    ];
+    rustifact::write_const!(DISCOUNTS, [Discount; 2], &discounts);
+    assert_eq!(CHECKED.load(std::sync::atomic::Ordering::Relaxed), 2);
+
+    let sorted = SortedIn {
+        ids: [30, 10, 20],
+        ranked: [("gold", 1), ("bronze", 3), ("silver", 2)],
+        ports: [80, 8080, 443],
+    };
+    rustifact::write_const!(SORTED, Sorted, &sorted);
+
+    let merged = MergedIn {
+        names: vec!["b", "a", "b", "c", "a"],
+        codes: vec![3, 1, 3, 2, 1],
+    };
+    rustifact::write_static!(MERGED, Merged, &merged);
+
+    let settings = SettingsIn {
+        settings: [
+            SettingIn { id: 1, value: 10 },
+            SettingIn { id: 2, value: 10 },
+        ],
+    };
+    rustifact::write_const!(SETTINGS, Settings, &settings);
+
+    let font = FontIn {
+        sizes: vec![8, 12],
+        scale: Some(1.5),
+        level: Level::Low,
+    };
+    rustifact::write_static!(FONT, Font, &font);
+
+    let mut b = NodeIn::leaf("b");
+    b.children.push(NodeIn::leaf("d"));
+    let mut c = NodeIn::leaf("c");
+    c.next = Some(Box::new(NodeIn::leaf("e")));
+    let mut a = NodeIn::leaf("a");
+    a.children = vec![b, c];
+    rustifact::write_static!(NODES, &[Node], &a);
+
+    let langs = [
+        LangIn::Rust,
+        LangIn::Experimental("hare".to_string()),
+        LangIn::Zig,
+        LangIn::Other,
+    ];
+    rustifact::write_const!(LANGS, [Lang; 4], &langs);
+
+    let probes = [
+        ProbeIn {
+            host: "a.example",
+            outcome: Ok(200),
+        },
+        ProbeIn {
+            host: "b.example",
+            outcome: Err("timed out".to_string()),
+        },
+    ];
+    rustifact::write_const!(PROBES, [Probe; 2], &probes);
+
+    let entries = [("b.example", vec![443]), ("a.example", vec![80, 8080])];
+    let routes = RoutesIn {
+        by_host: entries.into_iter().collect(),
+    };
+    let found = RoutesIn::rustifact_multimap_get(&[("a", &[1u8][..])], &"a");
+    assert_eq!(found, [1]);
+    rustifact::write_const!(ROUTES, Routes, &routes);
+
+    let mut pending = std::collections::VecDeque::from([2, 3]);
+    pending.push_front(7);
+    let queues = QueuesIn {
+        pending,
+        history: [9, 1, 5].into_iter().collect(),
+        urgent: [4, 8, 6].into_iter().collect(),
+    };
+    rustifact::write_const!(QUEUES, Queues, &queues);
+
+    let quota = QuotaIn {
+        retries: None,
+        timeout: Ok(30),
+        name: Some("strict"),
+    };
+    rustifact::write_const!(QUOTA, Quota, &quota);
+
+    let block = BlockIn {
+        lanes: vec![1, 2, 3],
+        labels: vec!["x"],
+    };
+    rustifact::write_const!(BLOCK, Block, &block);
+
+    let roster = RosterIn {
+        members: ["ann", "bo"],
+        scores: vec![7, 9, 8, 7],
+    };
+    rustifact::write_static!(ROSTER, Roster, &roster);
+
+    let rgb = RgbIn {
+        channels: vec![255, 128, 0],
+    };
+    rustifact::write_const!(RGB, Rgb, &rgb);
+
+    let small = PairIn { k: 7u8, v: 1 };
+    assert_eq!(small.key_tokens().to_string(), "7u8");
+    let pair = PairIn { k: 700u16, v: 2 };
+    let (k, pair) = pair.keyed();
+    assert_eq!(k, 700);
+    rustifact::write_static!(PAIR, Pair<u16>, pair);
+
+    let _ = GatedOff { opaque: Opaque }.opaque;
+    rustifact::write_const!(SHARED, Shared, &SharedIn { id: 3 });
+
+    let layout = LayoutIn {
+        name: "box".to_string(),
+        extent: ExtentIn { len: 3 },
+    };
+    rustifact::write_static!(LAYOUT, Layout, &layout);
+    let mut toks = TokenStream::new();
+    layout.to_toks_with(&Units { scale: 10 }, &mut toks);
+    write_items(
+        "LAYOUT_SCALED",
+        quote! { static LAYOUT_SCALED: Layout = #toks; },
+    );
+
+    let tree = TreeIn {
+        label: 't',
+        level: Level::Low,
+        path: vec![0, 2],
+        weights: [5, 6],
+        id: std::num::NonZeroU32::new(9).unwrap(),
+        ratio: 0.5,
+    };
+    rustifact::write_const!(TREE, Tree, &tree);
+
+    let budget = BudgetIn {
+        costs: [1, 2, 3, 4],
+        extra: None,
+    };
+    rustifact::write_const!(BUDGET, Budget, &budget);
+    let oversized = BudgetIn {
+        costs: [0; 4],
+        extra: Some([0; 16]),
+    };
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let emitted = std::panic::catch_unwind(|| oversized.to_tok_stream());
+    std::panic::set_hook(hook);
+    assert!(emitted.is_err());
+
+    let gauges = [GaugeIn { low: 0, high: 10 }, GaugeIn { low: 5, high: 50 }];
+    rustifact::write_const!(GAUGES, [Gauge; 2], &gauges);
+    write_items("GAUGES_METRICS", GaugeIn::emit_metrics(&gauges, "GAUGES"));
+    GaugeIn { low: 1, high: 2 }.write_artifact("GAUGE");
+
+    assert_eq!(SuitIn::ALL.len(), 4);
+    let suits = SuitIn::emit_all();
+    write_items("SUITS", quote! { static SUITS: &[Suit] = #suits; });
+
+    let shuffled = ShuffledIn {
+        ring: Ring(vec![3, 1, 2]),
+        unsorted: Ring(vec![3, 1, 2]),
+    };
+    rustifact::write_static!(SHUFFLED, Shuffled, &shuffled);
+
+    let interned = InternedIn {
+        boxed: "box".into(),
+        shared: "rc".into(),
+        atomic: "arc".into(),
+    };
+    rustifact::write_static!(INTERNED, Interned, &interned);
+    let interned_owned = InternedOwnedIn {
+        boxed: "box".into(),
+        atomic: "arc".into(),
+    }
+    .to_tok_stream();
+    write_items(
+        "INTERNED_OWNED",
+        quote! { fn interned_owned() -> InternedOwned { #interned_owned } },
+    );
+
+    let borrowed = BorrowedIn {
+        text: "plain".into(),
+        steps: vec![1, 2].into(),
+        limit: std::borrow::Cow::Owned(9),
+        label: String::from("kept").into(),
+    };
+    rustifact::write_static!(BORROWED, Borrowed, &borrowed);
+
+    let parent = std::rc::Rc::new(1);
+    let shared = std::sync::Arc::new(2);
+    let linked = LinkedIn {
+        id: 3,
+        parent: std::rc::Rc::downgrade(&parent),
+        shared: std::sync::Arc::downgrade(&shared),
+    }
+    .to_tok_stream();
+    write_items("LINKED", quote! { fn linked() -> Linked { #linked } });
+
+    let offsets = OffsetsIn {
+        start: 7,
+        ends: [10, 20],
+        last: Some(30),
+        delta: -4,
+    };
+    rustifact::write_const!(OFFSETS, Offsets, &offsets);
+
+    let counters = CountersIn {
+        hits: std::sync::atomic::AtomicU32::new(0),
+        ready: std::sync::atomic::AtomicBool::new(false),
+    };
+    counters
+        .hits
+        .fetch_add(5, std::sync::atomic::Ordering::Relaxed);
+    counters
+        .ready
+        .store(true, std::sync::atomic::Ordering::Relaxed);
+    rustifact::write_static!(COUNTERS, Counters, &counters);
+
+    let locked = LockedIn {
+        queue: std::sync::Mutex::new([1, 2]),
+        name: std::sync::RwLock::new("lock"),
+    };
+    rustifact::write_static!(LOCKED, Locked, &locked);
+    let celled = CelledIn {
+        count: std::cell::Cell::new(4),
+        level: std::cell::RefCell::new(Level::Low),
+    };
+    *celled.level.borrow_mut() = Level::High(2);
+    let celled = celled.to_tok_stream();
+    write_items("CELLED", quote! { fn celled() -> Celled { #celled } });
+
+    let cached = CachedIn {
+        words: ["a", "bcd", "ef"],
+        longest: std::sync::OnceLock::new(),
+        total: std::sync::OnceLock::new(),
+        first: std::cell::OnceCell::new(),
+    };
+    cached.total.set(6).unwrap();
+    cached.first.set("a").unwrap();
+    rustifact::write_static!(CACHED, Cached, &cached);
+
+    let handlers = HandlersIn {
+        on_x: double,
+        table: [triple, double],
+    };
+    rustifact::write_const!(HANDLERS, Handlers, &handlers);
+
+    let first = StageIn {
+        name: "first".to_string(),
+        steps: vec![1, 2],
+        weight: 3,
+    };
+    assert!(first.to_tok_stream().to_string().contains("Stage"));
+    // As if included from the artifact of the first stage.
+    let stage = Stage {
+        name: "first",
+        steps: &[1, 2],
+        weight: 3,
+    };
+    rustifact::write_static!(STAGE, Stage, &stage);
+
+    let figures = [
+        FigureIn::Circle { radius: 4 },
+        FigureIn::Rect { w: 2, h: 3 },
+        FigureIn::Dot,
+    ];
+    rustifact::write_const!(FIGURES, [Figure; 3], &figures);
+
+    let commands = CommandsIn {
+        by_name: [("go", 1), ("goto", 2), ("help", 3)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    };
+    rustifact::write_static!(COMMANDS, Commands, &commands);
+    let lookup = |key: &str| {
+        CommandsIn::rustifact_trie_longest_prefix(&[(&[(b'a', 1)], None), (&[], Some(7u8))], key)
+            .map(|(len, v)| (len, *v))
+    };
+    assert_eq!((lookup("ab"), lookup("b")), (Some((1, 7)), None));
+
+    let currencies = CurrenciesIn {
+        by_code: [("USD", 840), ("EUR", 978), ("JPY", 392)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+        by_digit: [(8, '$'), (9, '€')].into_iter().collect(),
+    };
+    let mut entries: Vec<(String, u16)> = currencies
+        .by_code
+        .iter()
+        .map(|(k, v)| (k.clone(), *v))
+        .collect();
+    entries.sort();
+    assert_eq!(
+        CurrenciesIn::rustifact_lookup_get(&entries, "JPY"),
+        Some(&392)
+    );
+    rustifact::write_static!(CURRENCIES, Currencies, &currencies);
+
+    let lexer = LexerIn {
+        table: vec![vec![1, 0], vec![], vec![1, 2]],
+        named: [(('a', "go"), 2), (('a', "stop"), 0), (('b', "go"), 3)]
+            .iter()
+            .map(|((s, i), t)| ((*s, i.to_string()), *t))
+            .collect(),
+    };
+    rustifact::write_const!(LEXER, Lexer, &lexer);
+
+    let packed = Packed {
+        samples: vec![7; 4096],
+    };
+    rustifact::write_static!(PACKED, std::sync::LazyLock<Packed>, &packed);
+}
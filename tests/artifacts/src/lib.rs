@@ -0,0 +1,1516 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! The out types of the artifacts emitted by the build script, which the tests include, so that
+//! the emission of each derive is compiled and checked against them.
+
+use serde::Deserialize;
+
+// Artifacts name the types of other crates by their paths, which here name stand-ins for them.
+extern crate self as chrono;
+extern crate self as time;
+extern crate self as uuid;
+
+#[derive(Deserialize)]
+pub struct Encoded {
+    pub rows: Vec<(u32, u64)>,
+}
+
+pub struct Image {
+    pub pixels: &'static [u8],
+}
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// The tables, of which the big one is only emitted with the feature `big-tables`.
+pub struct Tables {
+    pub small: [u8; 2],
+    #[cfg(feature = "big-tables")]
+    pub big: [u8; 64],
+}
+
+pub struct __BindgenBitfieldUnit<Storage> {
+    pub storage: Storage,
+}
+
+impl<Storage> __BindgenBitfieldUnit<Storage> {
+    pub const fn new(storage: Storage) -> Self {
+        Self { storage }
+    }
+}
+
+pub struct Header {
+    pub len: u32,
+    pub _bitfield_align_1: [u8; 0],
+    pub _bitfield_1: __BindgenBitfieldUnit<[u8; 1usize]>,
+}
+
+#[derive(Clone, Copy)]
+pub union Word {
+    pub bytes: [u8; 2],
+    pub short: u16,
+}
+
+pub struct Converted {
+    pub timeout: std::time::Duration,
+    pub built: (u64, u32),
+    pub letters: std::ops::RangeInclusive<char>,
+    pub dir: &'static str,
+    pub addr: &'static str,
+    pub name_len: usize,
+}
+
+pub struct Limits {
+    pub max: std::num::NonZeroU32,
+    pub step: Option<std::num::NonZeroU16>,
+    pub sign: std::num::NonZeroI8,
+}
+
+pub struct Spans {
+    pub bytes: std::ops::Range<usize>,
+    pub digits: std::ops::RangeInclusive<char>,
+    pub nested: std::ops::Range<std::ops::RangeInclusive<u8>>,
+}
+
+pub struct Endpoints {
+    pub local: std::net::Ipv4Addr,
+    pub any: std::net::IpAddr,
+    pub v6: std::net::SocketAddrV6,
+    pub server: std::net::SocketAddr,
+}
+
+pub struct Floats {
+    pub third: f64,
+    pub nan: f32,
+    pub scale: Option<f32>,
+    pub bounds: std::ops::Range<f64>,
+}
+
+pub struct Labels {
+    pub title: String,
+    pub alt: Option<String>,
+}
+
+pub struct Label {
+    pub text: String,
+    pub key: &'static str,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Level {
+    Low,
+    High(u8),
+}
+
+pub struct Config {
+    pub name: &'static str,
+    pub levels: &'static [Level],
+    pub default: &'static Level,
+    pub path: &'static [u8],
+}
+
+pub struct Schema {
+    pub rows: [u32; 2],
+}
+
+pub struct Entry {
+    pub name: &'static str,
+    pub value: u32,
+}
+
+pub struct Sample {
+    pub id: u16,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Mode {
+    Fast,
+    Careful(u8),
+}
+
+pub struct Codes {
+    pub by_name: &'static [(&'static str, u16)],
+}
+
+pub struct Tags {
+    pub hashed: &'static [&'static str],
+    pub ordered: &'static [u8],
+}
+
+pub struct Link {
+    pub value: u8,
+    pub next: Option<&'static Link>,
+}
+
+pub struct Hosts {
+    pub addrs: [std::net::Ipv4Addr; 2],
+    pub steps: [Option<std::num::NonZeroU8>; 2],
+}
+
+pub struct Timed {
+    pub entry: (std::net::Ipv4Addr, std::time::Duration, u8),
+}
+
+pub struct Squares {
+    pub values: &'static [u32],
+}
+
+pub struct Flags {
+    pub set: &'static [(u32, u32)],
+}
+
+pub struct Unit {
+    pub symbol: char,
+}
+
+pub struct Palette {
+    pub colours: [u32; 2],
+}
+
+pub struct Kelvin(pub f64);
+
+pub struct Polygon {
+    pub sides: u8,
+}
+
+pub struct Drawing {
+    pub main: Polygon,
+    pub extra: Option<Polygon>,
+}
+
+pub struct Macroed {
+    pub value: Option<std::num::NonZeroU64>,
+}
+
+pub struct SelfLink {
+    pub value: u8,
+    pub next: Option<&'static SelfLink>,
+}
+
+pub struct Tagged {
+    pub value: u16,
+    pub label: usize,
+}
+
+pub struct Grid {
+    pub cells: [u8; 4],
+    pub edge: [u8; 3],
+}
+
+pub struct Table {
+    pub names: &'static [&'static str],
+    pub weights: &'static [Option<u16>; 3],
+    pub level: &'static Level,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Kind {
+    Known(u8),
+    Unknown,
+}
+
+pub struct Row {
+    pub id: u32,
+    pub weight: f32,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Glyph {
+    pub fields: u32,
+    pub code: u32,
+    pub printable: bool,
+}
+
+pub const DEFAULT_GLYPH: Glyph = Glyph {
+    fields: 0,
+    code: 0,
+    printable: true,
+};
+
+pub struct Costs {
+    pub by_op: &'static [(u8, u16)],
+}
+
+pub struct Digest {
+    pub bytes: [u8; 3],
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Opcode {
+    LoadConst,
+    JumpIf(u32),
+    Halt,
+}
+
+pub struct Local {
+    pub x: u8,
+}
+
+pub struct Chunked {
+    pub values: &'static [&'static [u16]],
+}
+
+pub struct Widened {
+    pub count: u64,
+    pub ratio: f64,
+    pub steps: [Option<i32>; 2],
+}
+
+pub struct Samples {
+    pub values: &'static [u8],
+}
+
+/// Out types named by their path in artifacts, rather than needing to be in scope.
+pub mod prefixed {
+    pub struct Prefixed {
+        pub id: u8,
+    }
+}
+
+pub struct View<'a> {
+    pub name: &'a str,
+}
+
+pub struct Keyed {
+    pub name: &'static str,
+    pub first: u8,
+    pub by: &'static [(u8, u16)],
+}
+
+pub struct Closed {
+    pub idle_secs: u32,
+    pub name_len: usize,
+}
+
+pub struct Rings {
+    pub plain: &'static [u8],
+    pub scaled: &'static [u16],
+}
+
+pub struct Ordered {
+    pub by_name: &'static [(&'static str, u8)],
+    pub sorted: &'static [(&'static str, u8)],
+    pub ids: &'static [u16],
+}
+
+pub struct Inline {
+    pub small: &'static [u8],
+    pub fixed: [u16; 3],
+}
+
+pub struct Strings {
+    pub smol: &'static str,
+    pub compact: Option<&'static str>,
+    pub label: &'static str,
+}
+
+/// Stands in for uuid's type, with the const constructor the artifacts call.
+#[derive(Debug, PartialEq)]
+pub struct Uuid(u128);
+
+impl Uuid {
+    pub const fn from_u128(n: u128) -> Self {
+        Uuid(n)
+    }
+}
+
+pub struct Device {
+    pub id: Uuid,
+}
+
+/// Stands in for chrono's and time's types, with the const constructors the artifacts call.
+#[derive(Debug, PartialEq)]
+pub struct NaiveDate(i32, u32, u32);
+
+impl NaiveDate {
+    pub const fn from_ymd_opt(year: i32, month: u32, day: u32) -> Option<Self> {
+        Some(NaiveDate(year, month, day))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Utc;
+
+#[derive(Debug, PartialEq)]
+pub struct DateTime<Tz>(i64, u32, Tz);
+
+impl DateTime<Utc> {
+    pub const fn from_timestamp(secs: i64, nanos: u32) -> Option<Self> {
+        Some(DateTime(secs, nanos, Utc))
+    }
+}
+
+#[derive(Debug)]
+pub struct ComponentRange;
+
+#[derive(Debug, PartialEq)]
+pub struct UtcOffset(i32);
+
+impl UtcOffset {
+    pub const fn from_whole_seconds(seconds: i32) -> Result<Self, ComponentRange> {
+        Ok(UtcOffset(seconds))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct OffsetDateTime(i128, i32);
+
+impl OffsetDateTime {
+    pub const fn from_unix_timestamp_nanos(nanos: i128) -> Result<Self, ComponentRange> {
+        Ok(OffsetDateTime(nanos, 0))
+    }
+
+    pub const fn to_offset(self, offset: UtcOffset) -> Self {
+        OffsetDateTime(self.0, offset.0)
+    }
+}
+
+pub struct Release {
+    pub date: NaiveDate,
+    pub built: DateTime<Utc>,
+    pub signed: OffsetDateTime,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Perms(u8);
+
+impl Perms {
+    pub const fn from_bits_retain(bits: u8) -> Self {
+        Perms(bits)
+    }
+}
+
+pub struct File {
+    pub name: &'static str,
+    pub perms: Perms,
+}
+
+pub struct Masks {
+    pub magic: u32,
+    pub low: i8,
+    pub mode: Option<u16>,
+    pub lanes: [u64; 2],
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Style {
+    pub size: u8,
+    pub spacing: u8,
+    pub flags: u8,
+}
+
+pub const DEFAULT_STYLE: Style = Style {
+    size: 12,
+    spacing: 1,
+    flags: 0,
+};
+
+/// Stands in for the compilation of a pattern, which can't run in a static.
+pub fn compile_pattern(source: &str) -> Vec<char> {
+    let (lo, hi) = source.split_once('-').unwrap();
+    (lo.chars().next().unwrap()..=hi.chars().next().unwrap()).collect()
+}
+
+pub struct Rule {
+    pub name: &'static str,
+    pub pattern: std::sync::LazyLock<Vec<char>>,
+}
+
+pub struct Discount {
+    pub code: &'static str,
+    pub percent: u8,
+}
+
+pub struct Sorted {
+    pub ids: [u32; 3],
+    pub ranked: [(&'static str, u8); 3],
+    pub ports: [u16; 3],
+}
+
+pub struct Merged {
+    pub names: &'static [&'static str],
+    pub codes: &'static [u8],
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Setting {
+    pub id: u8,
+    pub value: u16,
+}
+
+pub struct Settings {
+    pub settings: [Setting; 2],
+}
+
+pub struct Font {
+    pub sizes: &'static [u32],
+    pub scale: Option<f32>,
+    pub level: &'static Level,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct NodeId(pub u32);
+
+pub struct Node {
+    pub name: &'static str,
+    pub children: &'static [NodeId],
+    pub next: Option<NodeId>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Lang {
+    Rust,
+    Unlisted,
+}
+
+pub struct Probe {
+    pub host: &'static str,
+    pub outcome: Result<u16, &'static str>,
+}
+
+pub struct Routes {
+    pub by_host: &'static [(&'static str, &'static [u16])],
+}
+
+pub struct Queues {
+    pub pending: &'static [u8],
+    pub history: &'static [u8],
+    pub urgent: &'static [u8],
+}
+
+pub struct Quota {
+    pub retries: u8,
+    pub timeout: u32,
+    pub name: &'static str,
+}
+
+pub struct Block {
+    pub lanes: [u16; 4],
+    pub labels: [&'static str; 3],
+}
+
+pub struct Roster {
+    pub members: [&'static str; 2],
+    pub scores: &'static [u8],
+}
+
+pub struct Rgb {
+    pub channels: [u8; 3],
+}
+
+pub struct Pair<T> {
+    pub k: T,
+    pub v: u32,
+}
+
+pub struct Shared {
+    pub id: u8,
+}
+
+pub struct Extent {
+    pub len: u32,
+}
+
+pub struct Layout {
+    pub name: &'static str,
+    pub extent: Extent,
+}
+
+pub struct Tree {
+    pub label: char,
+    pub level: &'static Level,
+    pub path: &'static [u8],
+    pub weights: &'static [u16; 2],
+    pub id: std::num::NonZeroU32,
+    pub ratio: f32,
+}
+
+pub struct Budget {
+    pub costs: [u32; 4],
+    pub extra: Option<[u32; 16]>,
+}
+
+pub struct Gauge {
+    pub low: u16,
+    pub high: u16,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+pub struct Shuffled {
+    pub ring: &'static [u8],
+    pub unsorted: &'static [u8],
+}
+
+pub struct Interned {
+    pub boxed: &'static str,
+    pub shared: &'static str,
+    pub atomic: &'static str,
+}
+
+pub struct InternedOwned {
+    pub boxed: Box<str>,
+    pub atomic: std::sync::Arc<str>,
+}
+
+pub struct Borrowed {
+    pub text: &'static str,
+    pub steps: &'static [u16],
+    pub limit: &'static u32,
+    pub label: std::borrow::Cow<'static, str>,
+}
+
+pub struct Linked {
+    pub id: u8,
+    pub parent: std::rc::Weak<u8>,
+    pub shared: std::sync::Weak<u8>,
+}
+
+pub struct Offsets {
+    pub start: u32,
+    pub ends: [u32; 2],
+    pub last: Option<u32>,
+    pub delta: i16,
+}
+
+pub struct Counters {
+    pub hits: std::sync::atomic::AtomicU32,
+    pub ready: std::sync::atomic::AtomicBool,
+}
+
+pub struct Locked {
+    pub queue: std::sync::Mutex<[u8; 2]>,
+    pub name: std::sync::RwLock<&'static str>,
+}
+
+pub struct Celled {
+    pub count: std::cell::Cell<u16>,
+    pub level: std::cell::RefCell<Level>,
+}
+
+pub struct Cached {
+    pub words: [&'static str; 3],
+    pub longest: std::sync::OnceLock<usize>,
+    pub total: std::sync::LazyLock<usize>,
+    pub first: std::sync::LazyLock<&'static str>,
+}
+
+pub fn double(n: u32) -> u32 {
+    n * 2
+}
+
+pub fn triple(n: u32) -> u32 {
+    n * 3
+}
+
+pub struct Handlers {
+    pub on_x: fn(u32) -> u32,
+    pub table: [fn(u32) -> u32; 2],
+}
+
+pub struct Stage {
+    pub name: &'static str,
+    pub steps: &'static [u16],
+    pub weight: u8,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Figure {
+    Circle { radius: u16 },
+    Rect { w: u16, h: u16 },
+    Dot,
+}
+
+/// A node of a trie, with its edges and the value of the key ending there.
+pub type TrieNode<V> = (&'static [(u8, u32)], Option<V>);
+
+pub struct Commands {
+    pub by_name: &'static [TrieNode<u8>],
+}
+
+pub struct Currencies {
+    pub by_code: &'static [(&'static str, u16)],
+    pub by_digit: &'static [(u8, char)],
+}
+
+pub struct Lexer {
+    pub table: fn(usize, usize) -> Option<u8>,
+    pub named: fn(char, &str) -> Option<u16>,
+}
+
+#[derive(Deserialize)]
+pub struct Packed {
+    pub samples: Vec<u16>,
+}
+
+#[cfg(test)]
+// `unchecked` emits the constructors needed before `Option::unwrap` was const.
+#[allow(clippy::useless_nonzero_new_unchecked)]
+mod tests {
+    use super::*;
+
+    rustifact::use_symbols!(
+        ENCODED,
+        IMAGE,
+        BAKED,
+        TABLES,
+        HEADER,
+        WORD,
+        CONVERTED,
+        LIMITS,
+        SPANS,
+        ENDPOINTS,
+        FLOATS,
+        OWNED,
+        CONFIG,
+        CONFIG_CONST,
+        SCHEMA,
+        SCHEMA_VERSION,
+        ENTRIES,
+        SAMPLES,
+        SEVERITIES,
+        MODES,
+        CODES,
+        TAGS,
+        CHAIN,
+        HOSTS,
+        TIMED,
+        SQUARES,
+        FLAGS,
+        UNITS,
+        PALETTE,
+        BOILING,
+        DRAWING,
+        MACROED,
+        SELF_CHAIN,
+        TAGGED,
+        GRID,
+        TABLE,
+        TABLE_INLINE,
+        KINDS,
+        ROWS,
+        GLYPHS,
+        COSTS,
+        DIGEST,
+        DIGEST_SUM,
+        OPCODES,
+        LOCAL,
+        CHUNKED,
+        WIDENED,
+        SIDECAR,
+        PREFIXED,
+        TABLE_CONST,
+        SHADES,
+        VIEW,
+        KEYED,
+        CLOSED,
+        RINGS,
+        ORDERED,
+        INLINE,
+        STRINGS,
+        DEVICE,
+        RELEASE,
+        FILE,
+        MASKS,
+        STYLES,
+        RULE,
+        DISCOUNTS,
+        SORTED,
+        MERGED,
+        SETTINGS,
+        FONT,
+        NODES,
+        LANGS,
+        PROBES,
+        ROUTES,
+        QUEUES,
+        QUOTA,
+        BLOCK,
+        ROSTER,
+        RGB,
+        PAIR,
+        SHARED,
+        LAYOUT,
+        LAYOUT_SCALED,
+        TREE,
+        BUDGET,
+        GAUGES,
+        GAUGES_METRICS,
+        GAUGE,
+        SUITS,
+        SHUFFLED,
+        INTERNED,
+        INTERNED_OWNED,
+        BORROWED,
+        LINKED,
+        OFFSETS,
+        COUNTERS,
+        LOCKED,
+        CELLED,
+        CACHED,
+        HANDLERS,
+        STAGE,
+        FIGURES,
+        COMMANDS,
+        CURRENCIES,
+        LEXER,
+        PACKED
+    );
+
+    #[test]
+    fn encode() {
+        assert_eq!(ENCODED.rows, [(1, 10), (2, 20)]);
+    }
+
+    #[test]
+    fn encoder() {
+        assert_eq!(IMAGE.pixels, &[0, 127, 255]);
+    }
+
+    #[test]
+    fn bake() {
+        assert_eq!((BAKED.x, BAKED.y), (1, -2));
+    }
+
+    #[test]
+    fn cfg() {
+        assert_eq!(TABLES.small, [1, 2]);
+        #[cfg(feature = "big-tables")]
+        assert_eq!(TABLES.big.len(), 64);
+    }
+
+    #[test]
+    fn bindgen() {
+        assert_eq!(HEADER.len, 12);
+        assert_eq!(HEADER._bitfield_1.storage, [0b101]);
+        assert_eq!(unsafe { WORD.bytes }, [1, 2]);
+    }
+
+    #[test]
+    fn with_converters() {
+        assert_eq!(CONVERTED.timeout, std::time::Duration::new(3, 500));
+        assert_eq!(CONVERTED.built, (60, 7));
+        assert_eq!(CONVERTED.letters, 'a'..='f');
+        assert_eq!((CONVERTED.dir, CONVERTED.addr), ("data/tables", "10.0.0.1"));
+        assert_eq!(CONVERTED.name_len, 5);
+    }
+
+    #[test]
+    fn nonzero() {
+        assert_eq!(LIMITS.max.get(), 100);
+        assert_eq!(LIMITS.step.map(|s| s.get()), Some(5));
+        assert_eq!(LIMITS.sign.get(), -1);
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(SPANS.bytes, 2..8);
+        assert_eq!(SPANS.digits, '0'..='9');
+        assert_eq!(SPANS.nested, (1..=2)..(3..=4));
+    }
+
+    #[test]
+    fn net() {
+        assert_eq!(ENDPOINTS.local, std::net::Ipv4Addr::LOCALHOST);
+        assert_eq!(ENDPOINTS.any.to_string(), "2001:db8::1");
+        assert_eq!(
+            (
+                ENDPOINTS.v6.port(),
+                ENDPOINTS.v6.flowinfo(),
+                ENDPOINTS.v6.scope_id()
+            ),
+            (443, 1, 2)
+        );
+        assert_eq!(ENDPOINTS.server.to_string(), "192.168.0.1:8080");
+    }
+
+    #[test]
+    fn float_bits() {
+        assert_eq!(FLOATS.third.to_bits(), (1.0f64 / 3.0).to_bits());
+        assert_eq!(FLOATS.nan.to_bits(), 0x7fc0_0123);
+        assert_eq!(FLOATS.scale, Some(0.1));
+        assert!(FLOATS.bounds.start.is_sign_negative());
+        assert_eq!(FLOATS.bounds.end, f64::MAX);
+    }
+
+    #[test]
+    fn owned() {
+        let labels = labels();
+        assert_eq!(
+            (labels.title, labels.alt),
+            ("Title".into(), Some("alt".into()))
+        );
+        let label = label();
+        assert_eq!((label.text, label.key), ("text".to_string(), "k"));
+    }
+
+    #[test]
+    fn static_ref() {
+        for config in [&CONFIG, &CONFIG_CONST] {
+            assert_eq!(config.name, "main");
+            assert_eq!(config.levels, &[Level::Low, Level::High(3)]);
+            assert_eq!(
+                (config.default, config.path),
+                (&Level::High(1), &[0, 2][..])
+            );
+        }
+    }
+
+    const _: () = check_schema_version(3);
+
+    #[test]
+    fn version() {
+        assert_eq!((SCHEMA.rows, SCHEMA_VERSION), ([4, 5], 3));
+        assert!(std::panic::catch_unwind(|| check_schema_version(2)).is_err());
+    }
+
+    #[test]
+    fn key() {
+        assert_eq!(
+            ENTRIES.get("two").map(|e| (e.name, e.value)),
+            Some(("two", 2))
+        );
+        assert!(ENTRIES.get("four").is_none());
+    }
+
+    #[test]
+    fn stream() {
+        assert!(SAMPLES.iter().enumerate().all(|(i, s)| s.id as usize == i));
+    }
+
+    #[test]
+    fn variant_names() {
+        assert_eq!(SEVERITIES, ["Debug", "warning", "Error"]);
+        assert_eq!(MODES, [Mode::Fast, Mode::Careful(2)]);
+    }
+
+    #[test]
+    fn map_slice() {
+        assert_eq!(CODES.by_name, &[("gone", 410), ("moved", 301), ("ok", 200)]);
+    }
+
+    #[test]
+    fn sets() {
+        assert_eq!(
+            (TAGS.hashed, TAGS.ordered),
+            (&["a", "b", "c"][..], &[1, 2, 3][..])
+        );
+    }
+
+    #[test]
+    fn boxes() {
+        let next = CHAIN.next.unwrap();
+        assert_eq!((CHAIN.value, next.value, next.next.is_none()), (1, 2, true));
+    }
+
+    #[test]
+    fn arrays() {
+        assert_eq!(HOSTS.addrs[1], std::net::Ipv4Addr::BROADCAST);
+        assert_eq!(HOSTS.steps, [std::num::NonZeroU8::new(4), None]);
+    }
+
+    #[test]
+    fn tuples() {
+        let (addr, after, n) = TIMED.entry;
+        assert_eq!(addr, std::net::Ipv4Addr::new(1, 2, 3, 4));
+        assert_eq!((after.as_millis(), n), (1500, 9));
+    }
+
+    #[test]
+    fn iter() {
+        assert_eq!(SQUARES.values, &[1, 4, 9, 16]);
+    }
+
+    #[test]
+    fn runs() {
+        assert_eq!(FLAGS.set, &[(3, 7), (18, 20)]);
+    }
+
+    #[test]
+    fn helpers_hidden() {
+        let artifact = include_str!(concat!(
+            env!("OUT_DIR"),
+            "/rustifact_rustifact_derive_tests_CONFIG.rs"
+        ));
+        assert!(artifact
+            .contains("#[doc(hidden)]\n        #[allow(\n            non_upper_case_globals,"));
+    }
+
+    #[test]
+    fn group() {
+        assert_eq!([UNITS[0].symbol, UNITS[1].symbol], ['m', 's']);
+    }
+
+    const _: () = check_palette_version(2);
+
+    #[test]
+    fn writer() {
+        assert_eq!(
+            (PALETTE.colours, PALETTE_VERSION),
+            ([0xff0000, 0x00ff00], 2)
+        );
+    }
+
+    #[test]
+    fn container_with() {
+        assert_eq!(BOILING.0, 373.15);
+    }
+
+    #[test]
+    fn dyn_with() {
+        assert_eq!((DRAWING.main.sides, DRAWING.extra.is_none()), (4, true));
+    }
+
+    #[test]
+    fn macro_types() {
+        assert_eq!(MACROED.value.map(|v| v.get()), Some(42));
+    }
+
+    #[test]
+    fn self_types() {
+        let next = SELF_CHAIN.next.unwrap();
+        assert_eq!(
+            (SELF_CHAIN.value, next.value, next.next.is_none()),
+            (3, 4, true)
+        );
+    }
+
+    #[test]
+    fn generic_bounds() {
+        assert_eq!((TAGGED.value, TAGGED.label), (7, 5));
+    }
+
+    #[test]
+    fn const_expr_lengths() {
+        assert_eq!((GRID.cells, GRID.edge), ([1, 2, 3, 4], [5, 6, 7]));
+    }
+
+    #[test]
+    fn hoisted_fields() {
+        for table in [&TABLE, &TABLE_INLINE] {
+            assert_eq!(table.names, &["x", "y"]);
+            assert_eq!(table.weights, &[Some(1), None, Some(3)]);
+            assert_eq!(table.level, &Level::High(9));
+        }
+        let artifact = include_str!(concat!(
+            env!("OUT_DIR"),
+            "/rustifact_rustifact_derive_tests_TABLE.rs"
+        ));
+        assert!(artifact.contains("\nstatic __TABLE_NAMES: [&'static str; 2usize] = ["));
+        assert!(artifact.contains("\nstatic TABLE: Table = Table {"));
+        assert!(artifact.contains("names: &__TABLE_NAMES,"));
+    }
+
+    #[test]
+    fn non_exhaustive_enums() {
+        assert_eq!(KINDS, [Kind::Known(1), Kind::Unknown]);
+    }
+
+    #[test]
+    fn bulk_slices() {
+        assert_eq!(ROWS.len(), 4);
+        assert_eq!((ROWS[3].id, ROWS[3].weight), (3, 1.5));
+    }
+
+    #[test]
+    fn struct_update_base() {
+        let first = Glyph {
+            fields: 2,
+            code: 0,
+            printable: false,
+        };
+        let second = Glyph {
+            code: 65,
+            ..DEFAULT_GLYPH
+        };
+        assert_eq!(GLYPHS, [first, second]);
+        let artifact = include_str!(concat!(
+            env!("OUT_DIR"),
+            "/rustifact_rustifact_derive_tests_GLYPHS.rs"
+        ));
+        assert!(artifact.contains("..DEFAULT_GLYPH"));
+        assert!(!artifact.contains("printable: true"));
+    }
+
+    #[test]
+    fn sparse_maps() {
+        assert_eq!(COSTS.by_op, &[(2, 5), (4, 9)]);
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(DIGEST.bytes, [1, 2, 3]);
+        assert_eq!(DIGEST_CHECKSUM, DIGEST_SUM);
+    }
+
+    #[test]
+    fn variant_conventions() {
+        assert_eq!(
+            OPCODES,
+            [Opcode::LoadConst, Opcode::JumpIf(4), Opcode::Halt]
+        );
+    }
+
+    #[test]
+    fn local_types() {
+        assert_eq!(LOCAL.x, 11);
+    }
+
+    #[test]
+    fn chunked_collections() {
+        let lens: Vec<usize> = CHUNKED.values.iter().map(|c| c.len()).collect();
+        assert_eq!(lens, [2, 2, 1]);
+        let values: Vec<u16> = CHUNKED.values.iter().copied().flatten().copied().collect();
+        assert_eq!(values, [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn unsuffixed_numbers() {
+        assert_eq!(WIDENED.count, 200);
+        assert_eq!(WIDENED.ratio, 0.5);
+        assert_eq!(WIDENED.steps, [Some(3), None]);
+    }
+
+    #[test]
+    fn sidecar_files() {
+        let values: Vec<u32> = SIDECAR
+            .values
+            .chunks(4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        assert_eq!(values, [1, 70_000, 3]);
+    }
+
+    #[test]
+    fn out_type_paths() {
+        assert_eq!(PREFIXED.id, 5);
+        let artifact = include_str!(concat!(
+            env!("OUT_DIR"),
+            "/rustifact_rustifact_derive_tests_PREFIXED.rs"
+        ));
+        assert!(artifact.contains("crate::prefixed::Prefixed {"));
+    }
+
+    #[test]
+    fn emitted_items() {
+        assert_eq!(TABLE_CONST.names, &["x", "y"]);
+        assert_eq!(TABLE_CONST.level, &Level::High(9));
+        assert_eq!((SHADES.colours, SHADES_VERSION), ([0x0000ff, 0xffffff], 2));
+        check_shades_version(2);
+    }
+
+    #[test]
+    fn hoisted_docs() {
+        let artifact = include_str!(concat!(
+            env!("OUT_DIR"),
+            "/rustifact_rustifact_derive_tests_TABLE.rs"
+        ));
+        assert!(artifact.contains("/// The names of the rows.\nstatic __TABLE_NAMES"));
+    }
+
+    #[test]
+    fn out_type_arguments() {
+        assert_eq!(VIEW.name, "borrowed");
+    }
+
+    #[test]
+    fn generic_lifetimes() {
+        assert_eq!(
+            (KEYED.name, KEYED.first, KEYED.by),
+            ("keyed", 1, &[(2, 6)][..])
+        );
+    }
+
+    #[test]
+    fn closure_converters() {
+        assert_eq!((CLOSED.idle_secs, CLOSED.name_len), (90, 4));
+    }
+
+    #[test]
+    fn collections() {
+        assert_eq!(
+            (RINGS.plain, RINGS.scaled),
+            (&[3, 1, 2][..], &[100, 200][..])
+        );
+    }
+
+    #[test]
+    fn insertion_order() {
+        assert_eq!(ORDERED.by_name, &[("b", 1), ("a", 2)]);
+        assert_eq!(ORDERED.sorted, &[("a", 2), ("b", 1)]);
+        assert_eq!(ORDERED.ids, &[9, 4]);
+    }
+
+    #[test]
+    fn inline_vecs() {
+        assert_eq!((INLINE.small, INLINE.fixed), (&[1, 2][..], [5, 6, 7]));
+    }
+
+    #[test]
+    fn string_types() {
+        assert_eq!(
+            (STRINGS.smol, STRINGS.compact, STRINGS.label),
+            ("smol", Some("compact"), "own")
+        );
+    }
+
+    #[test]
+    fn uuids() {
+        assert_eq!(DEVICE.id, Uuid(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8));
+    }
+
+    #[test]
+    fn dates_and_times() {
+        assert_eq!(RELEASE.date, NaiveDate(2024, 2, 29));
+        assert_eq!(RELEASE.built, DateTime(1_700_000_000, 5, Utc));
+        assert_eq!(RELEASE.signed, OffsetDateTime(42, 3600));
+    }
+
+    #[test]
+    fn bitflags_keep_unknown_bits() {
+        assert_eq!(FILE.name, "run.sh");
+        assert_eq!(FILE.perms, Perms(0b1000_0101));
+    }
+
+    #[test]
+    fn radix_literals() {
+        assert_eq!(MASKS.magic, 0xDEAD_BEEF);
+        assert_eq!(MASKS.low, -0b101);
+        assert_eq!(MASKS.mode, Some(0o755));
+        assert_eq!(MASKS.lanes, [0xFF, 0x1_0000]);
+    }
+
+    #[test]
+    fn packed_bools() {
+        let first = Style {
+            flags: 0b11,
+            ..DEFAULT_STYLE
+        };
+        let second = Style {
+            size: 16,
+            ..DEFAULT_STYLE
+        };
+        assert_eq!(STYLES, [first, second]);
+        let artifact = include_str!(concat!(
+            env!("OUT_DIR"),
+            "/rustifact_rustifact_derive_tests_STYLES.rs"
+        ));
+        assert!(artifact.contains("..DEFAULT_STYLE"));
+    }
+
+    #[test]
+    fn runtime_init() {
+        assert_eq!(RULE.name, "digits");
+        assert_eq!(RULE.pattern.len(), 10);
+        assert_eq!(RULE.pattern[9], '9');
+    }
+
+    #[test]
+    fn validated_fields() {
+        assert_eq!(DISCOUNTS[0].code, "HALF");
+        assert_eq!(DISCOUNTS[1].percent, 100);
+    }
+
+    #[test]
+    fn sorted_collections() {
+        assert_eq!(SORTED.ids, [10, 20, 30]);
+        assert_eq!(SORTED.ranked, [("gold", 1), ("silver", 2), ("bronze", 3)]);
+        assert_eq!(SORTED.ports, [8080, 443, 80]);
+    }
+
+    #[test]
+    fn deduplicated_collections() {
+        assert_eq!(MERGED.names, ["b", "a", "c"]);
+        assert_eq!(MERGED.codes, [1, 2, 3]);
+    }
+
+    #[test]
+    fn unique_keys() {
+        assert_eq!(SETTINGS.settings[0], Setting { id: 1, value: 10 });
+        assert_eq!(SETTINGS.settings[1], Setting { id: 2, value: 10 });
+    }
+
+    #[test]
+    fn out_fields() {
+        assert_eq!((FONT.sizes, FONT.scale), (&[8, 12][..], Some(1.5)));
+        assert_eq!(FONT.level, &Level::Low);
+        let artifact = include_str!(concat!(
+            env!("OUT_DIR"),
+            "/rustifact_rustifact_derive_tests_FONT.rs"
+        ));
+        assert!(artifact.contains("sizes: &[8, 12],"));
+        assert!(artifact.contains("scale: Some(1.5),"));
+    }
+
+    #[test]
+    fn arena() {
+        let names: Vec<&str> = NODES.iter().map(|n| n.name).collect();
+        assert_eq!(names, ["a", "b", "c", "d", "e"]);
+        assert_eq!(NODES[0].children, [NodeId(1), NodeId(2)]);
+        assert_eq!(NODES[1].children, [NodeId(3)]);
+        assert_eq!(NODES[2].next, Some(NodeId(4)));
+        assert!(NODES[3].children.is_empty() && NODES[4].next.is_none());
+    }
+
+    #[test]
+    fn catch_all_variants() {
+        use Lang::*;
+        assert_eq!(LANGS, [Rust, Unlisted, Unlisted, Unlisted]);
+    }
+
+    #[test]
+    fn results() {
+        assert_eq!(PROBES[0].outcome, Ok(200));
+        assert_eq!(PROBES[1].outcome, Err("timed out"));
+    }
+
+    #[test]
+    fn multimaps() {
+        assert_eq!(
+            ROUTES.by_host,
+            [("a.example", &[80, 8080][..]), ("b.example", &[443][..])]
+        );
+    }
+
+    #[test]
+    fn lists_as_slices() {
+        assert_eq!(QUEUES.pending, [7, 2, 3]);
+        assert_eq!(QUEUES.history, [1, 5, 9]);
+        assert_eq!(QUEUES.urgent, [4, 6, 8]);
+    }
+
+    #[test]
+    fn unwrapped_options() {
+        assert_eq!(QUOTA.retries, 0);
+        assert_eq!(QUOTA.timeout, 30);
+        assert_eq!(QUOTA.name, "strict");
+    }
+
+    #[test]
+    fn padded_collections() {
+        assert_eq!(BLOCK.lanes, [1, 2, 3, 0]);
+        assert_eq!(BLOCK.labels, ["x", "-", "-"]);
+    }
+
+    #[test]
+    fn length_constraints() {
+        assert_eq!(ROSTER.members, ["ann", "bo"]);
+        assert_eq!(ROSTER.scores, [7, 9, 8, 7]);
+    }
+
+    #[test]
+    fn exact_length_arrays() {
+        const CHANNELS: [u8; 3] = RGB.channels;
+        assert_eq!(CHANNELS, [255, 128, 0]);
+    }
+
+    #[test]
+    fn impl_for() {
+        assert_eq!((PAIR.k, PAIR.v), (700, 2));
+    }
+
+    #[test]
+    fn impl_cfg() {
+        assert_eq!(SHARED.id, 3);
+    }
+
+    #[test]
+    fn context() {
+        assert_eq!((LAYOUT.name, LAYOUT.extent.len), ("box", 3));
+        assert_eq!((LAYOUT_SCALED.name, LAYOUT_SCALED.extent.len), ("box", 30));
+    }
+
+    #[test]
+    fn msrv_fallbacks() {
+        assert_eq!(
+            (TREE.label, TREE.level, TREE.path, TREE.weights),
+            ('t', &Level::Low, &[0, 2][..], &[5, 6])
+        );
+        assert_eq!((TREE.id.get(), TREE.ratio), (9, 0.5));
+        let artifact = include_str!(concat!(
+            env!("OUT_DIR"),
+            "/rustifact_rustifact_derive_tests_TREE.rs"
+        ));
+        assert!(artifact.contains("NonZeroU32"));
+        assert!(!artifact.contains("static"));
+    }
+
+    #[test]
+    fn max_tokens() {
+        assert_eq!((BUDGET.costs, BUDGET.extra), ([1, 2, 3, 4], None));
+    }
+
+    #[test]
+    fn metrics() {
+        assert_eq!((GAUGES[1].high, GAUGES_ENTRY_COUNT), (50, 2));
+        assert_eq!(GAUGES_APPROX_TOKENS, 2 * GAUGE_APPROX_TOKENS);
+        assert_eq!((GAUGE.low, GAUGE.high), (1, 2));
+    }
+
+    #[test]
+    fn all_variants() {
+        assert_eq!(
+            SUITS,
+            [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades]
+        );
+    }
+
+    #[test]
+    fn sorted_collection_fields() {
+        assert_eq!(
+            (SHUFFLED.ring, SHUFFLED.unsorted),
+            (&[1, 2, 3][..], &[3, 1, 2][..])
+        );
+    }
+
+    #[test]
+    fn str_pointers() {
+        assert_eq!(
+            (INTERNED.boxed, INTERNED.shared, INTERNED.atomic),
+            ("box", "rc", "arc")
+        );
+        let owned = interned_owned();
+        assert_eq!((&*owned.boxed, &*owned.atomic), ("box", "arc"));
+    }
+
+    #[test]
+    fn cow_fields() {
+        assert_eq!(
+            (BORROWED.text, BORROWED.steps, *BORROWED.limit),
+            ("plain", &[1, 2][..], 9)
+        );
+        assert!(matches!(BORROWED.label, std::borrow::Cow::Borrowed("kept")));
+    }
+
+    #[test]
+    fn weak_fields() {
+        let linked = linked();
+        assert_eq!(linked.id, 3);
+        assert!(linked.parent.upgrade().is_none() && linked.shared.upgrade().is_none());
+    }
+
+    #[test]
+    fn usize_as() {
+        assert_eq!(
+            (OFFSETS.start, OFFSETS.ends, OFFSETS.last, OFFSETS.delta),
+            (7, [10, 20], Some(30), -4)
+        );
+    }
+
+    #[test]
+    fn atomics() {
+        use std::sync::atomic::Ordering;
+
+        assert!(COUNTERS.ready.load(Ordering::Relaxed));
+        assert_eq!(COUNTERS.hits.fetch_add(1, Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn interior_mutability() {
+        assert_eq!(*LOCKED.queue.lock().unwrap(), [1, 2]);
+        assert_eq!(*LOCKED.name.read().unwrap(), "lock");
+        let celled = celled();
+        assert_eq!(
+            (celled.count.get(), celled.level.into_inner()),
+            (4, Level::High(2))
+        );
+    }
+
+    #[test]
+    fn once_cells() {
+        let longest = CACHED
+            .longest
+            .get_or_init(|| CACHED.words.iter().map(|w| w.len()).max().unwrap());
+        assert_eq!((*longest, *CACHED.total, *CACHED.first), (3, 6, "a"));
+    }
+
+    #[test]
+    fn fn_paths() {
+        assert_eq!(
+            [
+                (HANDLERS.on_x)(5),
+                (HANDLERS.table[0])(5),
+                (HANDLERS.table[1])(5)
+            ],
+            [10, 15, 10]
+        );
+    }
+
+    #[test]
+    fn chain() {
+        assert_eq!(
+            (STAGE.name, STAGE.steps, STAGE.weight),
+            ("first", &[1, 2][..], 3)
+        );
+    }
+
+    #[test]
+    fn named_variant_fields() {
+        assert_eq!(
+            FIGURES,
+            [
+                Figure::Circle { radius: 4 },
+                Figure::Rect { w: 2, h: 3 },
+                Figure::Dot
+            ]
+        );
+    }
+
+    #[test]
+    fn trie() {
+        let nodes = COMMANDS.by_name;
+        let value = |key: &str| {
+            let mut node = &nodes[0];
+            for b in key.bytes() {
+                let (_, next) = node.0.iter().find(|e| e.0 == b)?;
+                node = &nodes[*next as usize];
+            }
+            node.1
+        };
+        assert_eq!(
+            (value("go"), value("goto"), value("help"), value("got")),
+            (Some(1), Some(2), Some(3), None)
+        );
+        assert_eq!(nodes[0].0.iter().map(|e| e.0).collect::<Vec<_>>(), b"gh");
+    }
+
+    #[test]
+    fn binary_search_lookup() {
+        let get = |code: &str| {
+            let entries = CURRENCIES.by_code;
+            let i = entries.binary_search_by_key(&code, |e| e.0).ok()?;
+            Some(entries[i].1)
+        };
+        assert_eq!(
+            (get("EUR"), get("USD"), get("GBP")),
+            (Some(978), Some(840), None)
+        );
+        assert_eq!(CURRENCIES.by_digit, [(8, '$'), (9, '€')]);
+    }
+
+    #[test]
+    fn state_machine() {
+        assert_eq!((LEXER.table)(0, 1), Some(0));
+        assert_eq!((LEXER.table)(1, 0), None);
+        assert_eq!((LEXER.table)(2, 1), Some(2));
+        assert_eq!((LEXER.table)(3, 0), None);
+        assert_eq!((LEXER.named)('a', "stop"), Some(0));
+        assert_eq!((LEXER.named)('b', "go"), Some(3));
+        assert_eq!((LEXER.named)('b', "stop"), None);
+    }
+
+    #[test]
+    fn zstd_compression() {
+        assert_eq!(PACKED.samples, [7; 4096]);
+    }
+}
@@ -0,0 +1,28 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Checks of the tokens emitted for values against the out types of the crate.
+
+use rustifact::ToTokenStream;
+use rustifact_derive::rustifact_assert_emits;
+use rustifact_derive_tests::{Font, Level};
+
+#[derive(ToTokenStream)]
+#[OutType(Level)]
+pub enum LevelIn {
+    Low,
+    High(u8),
+}
+
+#[test]
+fn emits_levels() {
+    rustifact_assert_emits!(LevelIn::Low, Level);
+    rustifact_assert_emits!(LevelIn::High(3), Level);
+}
+
+#[test]
+#[should_panic(expected = "don't type check as a constant of type Font")]
+fn rejects_other_types() {
+    rustifact_assert_emits!(LevelIn::High(3), Font);
+}